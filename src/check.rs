@@ -0,0 +1,327 @@
+//! 磁盘预算检查
+//!
+//! 本模块读取规则文件（目录 glob → 最大大小 / 最大文件数），在扫描得到的 [`Node`] 树中查找超出预算的目录，供 CI 使用。
+//!
+//! 规则文件示例：
+//!
+//! ```toml
+//! [[rule]]
+//! path = "target"
+//! max_size = "500M"
+//!
+//! [[rule]]
+//! path = "**/node_modules"
+//! max_files = 10000
+//! ```
+
+use crate::model::{Node, NodeKind::*};
+use crate::pattern::glob_match;
+use crate::size::SizeFormat;
+use anyhow::{Context, bail};
+use serde::Deserialize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 规则文件
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<RawRule>,
+}
+
+/// 规则文件中的原始规则
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRule {
+    path: String,
+    max_size: Option<SizeLimit>,
+    max_files: Option<usize>,
+}
+
+/// 大小限制（字节数或带单位的字符串）
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SizeLimit {
+    Bytes(u64),
+    Text(String),
+}
+
+/// 预算规则
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub path: String,             // 相对扫描根的目录 glob，根目录为 `.`
+    pub max_size: Option<u64>,    // 最大字节数
+    pub max_files: Option<usize>, // 最大文件数（递归）
+}
+
+/// 超出的预算项
+#[derive(Debug, Clone, Copy)]
+pub enum Exceeded {
+    Size { actual: u64, limit: u64 },
+    Files { actual: usize, limit: usize },
+}
+
+/// 违规记录
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub path: PathBuf,
+    pub rule: String,
+    pub exceeded: Exceeded,
+}
+
+/// 从文件加载规则
+pub fn load_rules(path: &Path) -> anyhow::Result<Vec<Rule>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Cannot read rules file {}", path.display()))?;
+    let file: RulesFile =
+        toml::from_str(&text).with_context(|| format!("Invalid rules file {}", path.display()))?;
+
+    file.rule
+        .into_iter()
+        .map(|raw| {
+            if raw.max_size.is_none() && raw.max_files.is_none() {
+                bail!("Rule `{}` sets neither max_size nor max_files", raw.path);
+            }
+            let max_size = match raw.max_size {
+                None => None,
+                Some(SizeLimit::Bytes(bytes)) => Some(bytes),
+                Some(SizeLimit::Text(text)) => {
+                    Some(parse_size(&text).with_context(|| format!("Rule `{}`", raw.path))?)
+                }
+            };
+            Ok(Rule {
+                path: raw.path,
+                max_size,
+                max_files: raw.max_files,
+            })
+        })
+        .collect()
+}
+
+/// 解析带单位的大小（`512`、`10K`、`1.5G`、`2GiB`，按 1024 进制）
+pub fn parse_size(text: &str) -> anyhow::Result<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .with_context(|| format!("Invalid size `{text}`"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => bail!("Unknown size unit in `{text}`"),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// 检查整棵树，返回所有违规
+pub fn check(root: &Node, rules: &[Rule]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    match root.kind() {
+        // 多个扫描根时，规则分别相对各自的根匹配
+        Directory(prop) if root.is_synthetic() => {
+            for scan_root in prop.children() {
+                check_recursive(scan_root, scan_root.path(), rules, &mut violations);
+            }
+        }
+        _ => {
+            check_recursive(root, root.path(), rules, &mut violations);
+        }
+    }
+    violations
+}
+
+/// 递归检查目录，返回子树中的文件数
+///
+/// 文件数自底向上累加，避免对每个匹配的目录重新遍历子树；
+/// 目录自身的违规插在其子目录的违规之前，保持先序输出。
+fn check_recursive(
+    node: &Node,
+    root: &Path,
+    rules: &[Rule],
+    violations: &mut Vec<Violation>,
+) -> usize {
+    let Directory(prop) = node.kind() else {
+        return 1;
+    };
+
+    let at = violations.len();
+    let files: usize = prop
+        .children()
+        .iter()
+        .map(|child| check_recursive(child, root, rules, violations))
+        .sum();
+
+    let relative = relative_path(root, node.path());
+    let mut own = Vec::new();
+    for rule in rules.iter().filter(|r| rule_matches(&r.path, &relative)) {
+        if let Some(limit) = rule.max_size
+            && node.size() > limit
+        {
+            own.push(Violation {
+                path: node.path().to_path_buf(),
+                rule: rule.path.clone(),
+                exceeded: Exceeded::Size {
+                    actual: node.size(),
+                    limit,
+                },
+            });
+        }
+        if let Some(limit) = rule.max_files
+            && files > limit
+        {
+            own.push(Violation {
+                path: node.path().to_path_buf(),
+                rule: rule.path.clone(),
+                exceeded: Exceeded::Files {
+                    actual: files,
+                    limit,
+                },
+            });
+        }
+    }
+    violations.splice(at..at, own);
+    files
+}
+
+/// 判断规则是否作用于目录
+///
+/// 扫描根只匹配 `.` 与全由 `**` 组成的规则；`*` 只匹配根下第一层，不包括根本身。
+fn rule_matches(pattern: &str, relative: &str) -> bool {
+    if relative == "." {
+        pattern == "." || pattern.trim_end_matches('/').split('/').all(|s| s == "**")
+    } else {
+        glob_match(pattern, relative)
+    }
+}
+
+/// 相对扫描根的路径（`/` 分隔，根为 `.`）
+fn relative_path(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    if relative.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// 输出可读的检查报告
+pub fn write_report(
+    root: &Node,
+    rules: &[Rule],
+    violations: &[Violation],
+    format: SizeFormat,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    if violations.is_empty() {
+        return writeln!(
+            out,
+            "✓ {}: all directories within budget ({} rules)",
+            root.root_label(),
+            rules.len()
+        );
+    }
+
+    writeln!(
+        out,
+        "✗ {}: {} budget violation(s)",
+        root.root_label(),
+        violations.len()
+    )?;
+    for violation in violations {
+        let detail = match violation.exceeded {
+            Exceeded::Size { actual, limit } => format!(
+                "size {} exceeds {} (+{})",
+                format.format(actual),
+                format.format(limit),
+                format.format(actual - limit)
+            ),
+            Exceeded::Files { actual, limit } => {
+                format!("{actual} files exceeds {limit} (+{})", actual - limit)
+            }
+        };
+        writeln!(
+            out,
+            "  {}  [{}]  {}",
+            relative_path(root.path(), &violation.path),
+            violation.rule,
+            detail
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_units() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("10K").unwrap(), 10 << 10);
+        assert_eq!(parse_size("10MiB").unwrap(), 10 << 20);
+        assert_eq!(parse_size("1.5G").unwrap(), 3 << 29);
+        assert_eq!(parse_size(" 2 TB ").unwrap(), 2 << 40);
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("G").is_err());
+    }
+
+    #[test]
+    fn root_matches_only_dot_and_globstar() {
+        assert!(rule_matches(".", "."));
+        assert!(rule_matches("**", "."));
+        assert!(rule_matches("**/", "."));
+        assert!(!rule_matches("*", "."));
+        assert!(!rule_matches("target", "."));
+        assert!(rule_matches("*", "target"));
+        assert!(!rule_matches("*", "target/debug"));
+        assert!(rule_matches("**/node_modules", "node_modules"));
+        assert!(rule_matches("**/node_modules", "web/app/node_modules"));
+    }
+
+    #[test]
+    fn check_reports_size_and_file_budgets() {
+        let dir = std::env::temp_dir().join(format!("fswhy-check-{}", std::process::id()));
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        for name in ["a/b/1", "a/b/2", "a/b/3", "a/4"] {
+            fs::write(dir.join(name), "xx").unwrap();
+        }
+        let root = Node::scan(dir.clone()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let rules = vec![
+            Rule {
+                path: "*".to_string(),
+                max_size: None,
+                max_files: Some(3),
+            },
+            Rule {
+                path: "a/**/b".to_string(),
+                max_size: Some(5),
+                max_files: None,
+            },
+        ];
+        let found: Vec<(PathBuf, String)> = check(&root, &rules)
+            .into_iter()
+            .map(|v| (v.path.strip_prefix(&dir).unwrap().to_path_buf(), v.rule))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (PathBuf::from("a"), "*".to_string()),
+                (PathBuf::from("a/b"), "a/**/b".to_string()),
+            ]
+        );
+    }
+}
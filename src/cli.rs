@@ -0,0 +1,254 @@
+//! 命令行解析
+//!
+//! 本模块将命令行参数解析为 [`Cli`]，负责校验选项与参数，并提供 `--help` 文本。
+
+use crate::model::ScanOptions;
+use crate::report::ReportOptions;
+use crate::{SizeUnits, SortMode, UiOptions};
+use anyhow::{anyhow, bail};
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// 帮助文本
+pub const HELP: &str = "\
+fswhy - interactive disk usage analyzer
+
+Usage: fswhy [OPTIONS] [PATH]...
+       fswhy check [OPTIONS] <RULES> [PATH]...
+
+Commands:
+  check <RULES>             Exit with status 1 if any directory exceeds its budget
+
+Options:
+  -h, --help                Print help
+  -V, --version             Print version
+      --theme <FILE>        Theme file (default: $FSWHY_THEME or ./theme.toml)
+      --config <FILE>       Config file (default: $FSWHY_CONFIG or ./fswhy.toml)
+  -s, --sort <MODE>         Sort mode: size | name [default: size]
+      --units <UNITS>       Size units: iec (KiB, MiB…) | si (kB, MB…) | bytes [default: iec]
+      --precision <N>       Decimal places for sizes [default: 1]
+      --dry-run             Only report what deleting or trashing in the interactive view would remove
+
+Scan options:
+  -e, --exclude <GLOB>      Skip entries whose name or relative path matches (repeatable)
+  -x, --one-file-system     Do not descend into other filesystems
+      --stdin               Read the paths to account from stdin, one per line
+  -0, --null                Paths on stdin are NUL-separated (implies --stdin)
+
+Output options (skip the interactive view):
+      --print               Print the tree to stdout
+      --markdown            Print a Markdown report to stdout
+      --folded <FILE>       Write folded stacks for flamegraph tools (- for stdout)
+      --prometheus <FILE>   Write Prometheus textfile metrics (- for stdout)
+  -d, --depth <N>           Maximum depth for printed trees, reports and metrics
+  -n, --top <N>             Entries per directory (--print) or table rows (--markdown)
+";
+
+/// 子命令
+#[derive(Clone, Debug)]
+pub enum Command {
+    Interactive,              // 交互界面（默认）
+    Check { rules: PathBuf }, // 预算检查
+}
+
+/// 非交互输出
+#[derive(Clone, Debug)]
+pub enum Output {
+    Tree,               // 纯文本树
+    Markdown,           // Markdown 报告
+    Folded(String),     // 折叠栈，`-` 为标准输出
+    Prometheus(String), // Prometheus 指标，`-` 为标准输出
+}
+
+/// 标准输入路径列表的分隔符
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathSeparator {
+    Newline, // 每行一个路径
+    Nul,     // NUL 分隔（find -print0、git ls-files -z）
+}
+
+/// 解析后的命令行
+#[derive(Clone, Debug)]
+pub struct Cli {
+    pub command: Command,
+    pub paths: Vec<PathBuf>,
+    pub scan: ScanOptions,
+    pub stdin: Option<PathSeparator>,
+    pub ui: UiOptions,
+    pub report: ReportOptions,
+    pub outputs: Vec<Output>,
+}
+
+/// 解析结果
+#[derive(Clone, Debug)]
+pub enum Parsed {
+    Run(Box<Cli>),
+    Help,
+    Version,
+}
+
+/// 解析命令行参数（不含程序名）
+pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Parsed> {
+    let mut args = args.into_iter().peekable();
+    let mut cli = Cli {
+        command: Command::Interactive,
+        paths: Vec::new(),
+        scan: ScanOptions::default(),
+        stdin: None,
+        ui: UiOptions::default(),
+        report: ReportOptions::default(),
+        outputs: Vec::new(),
+    };
+    let mut positionals: Vec<String> = Vec::new();
+
+    if args.peek().is_some_and(|arg| arg == "check") {
+        args.next();
+        cli.command = Command::Check {
+            rules: PathBuf::new(),
+        };
+    }
+
+    let mut only_positionals = false;
+    while let Some(arg) = args.next() {
+        if only_positionals || arg == "-" || !arg.starts_with('-') {
+            positionals.push(arg);
+            continue;
+        }
+        if arg == "--" {
+            only_positionals = true;
+            continue;
+        }
+
+        // 支持 `--name=value` 形式
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => {
+                (name.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        if inline_value.is_some()
+            && matches!(
+                name.as_str(),
+                "--help"
+                    | "--version"
+                    | "--one-file-system"
+                    | "--stdin"
+                    | "--null"
+                    | "--print"
+                    | "--markdown"
+                    | "--dry-run"
+            )
+        {
+            bail!("{name} does not take a value");
+        }
+        let mut value = |what: &str| -> anyhow::Result<String> {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| anyhow!("{name} requires {what}"))
+        };
+
+        match name.as_str() {
+            "-h" | "--help" => return Ok(Parsed::Help),
+            "-V" | "--version" => return Ok(Parsed::Version),
+            "--theme" => cli.ui.theme_path = Some(PathBuf::from(value("a file")?)),
+            "--config" => cli.ui.config_path = Some(PathBuf::from(value("a file")?)),
+            "-s" | "--sort" => cli.ui.sort_mode = parse_sort_mode(&value("a sort mode")?)?,
+            "--dry-run" => cli.ui.dry_run = true,
+            "--units" => cli.ui.size_format.units = SizeUnits::parse(&value("units")?)?,
+            "--precision" => {
+                cli.ui.size_format.precision = parse_number(&name, &value("a number")?)?
+            }
+            "-e" | "--exclude" => cli.scan.exclude.push(value("a glob")?),
+            "-x" | "--one-file-system" => cli.scan.one_file_system = true,
+            "--stdin" => {
+                cli.stdin.get_or_insert(PathSeparator::Newline);
+            }
+            "-0" | "--null" => cli.stdin = Some(PathSeparator::Nul),
+            "--print" => cli.outputs.push(Output::Tree),
+            "--markdown" => cli.outputs.push(Output::Markdown),
+            "--folded" => cli.outputs.push(Output::Folded(value("an output file")?)),
+            "--prometheus" => cli
+                .outputs
+                .push(Output::Prometheus(value("an output file")?)),
+            "-d" | "--depth" => {
+                cli.report.max_depth = Some(parse_number(&name, &value("a number")?)?)
+            }
+            "-n" | "--top" => cli.report.top = Some(parse_number(&name, &value("a number")?)?),
+            _ => bail!("unknown option '{arg}'"),
+        }
+    }
+    cli.report.sort_mode = cli.ui.sort_mode;
+    cli.report.size_format = cli.ui.size_format;
+    cli.scan.size_format = cli.ui.size_format;
+
+    let mut positionals = positionals.into_iter();
+    if let Command::Check { rules } = &mut cli.command {
+        *rules = positionals
+            .next()
+            .map(PathBuf::from)
+            .ok_or_else(|| anyhow!("check requires a rules file: fswhy check <RULES> [PATH]..."))?;
+        if !cli.outputs.is_empty() {
+            bail!("output options cannot be combined with check");
+        }
+    }
+    cli.paths = positionals.map(PathBuf::from).collect();
+    if cli.stdin.is_some() {
+        if !cli.paths.is_empty() {
+            bail!("paths cannot be given together with --stdin");
+        }
+        if !cli.scan.exclude.is_empty() || cli.scan.one_file_system {
+            bail!("scan options cannot be combined with --stdin");
+        }
+    }
+
+    Ok(Parsed::Run(Box::new(cli)))
+}
+
+/// 解析排序模式
+fn parse_sort_mode(value: &str) -> anyhow::Result<SortMode> {
+    match value {
+        "size" => Ok(SortMode::SizeDesc),
+        "name" => Ok(SortMode::NameAsc),
+        _ => bail!("invalid sort mode '{value}' (expected size or name)"),
+    }
+}
+
+/// 解析数值参数
+fn parse_number(flag: &str, value: &str) -> anyhow::Result<usize> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("{flag}: invalid number '{value}'"))
+}
+
+/// 读取路径列表，忽略空条目
+pub fn read_path_list(mut input: impl Read, separator: PathSeparator) -> io::Result<Vec<PathBuf>> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+    let delimiter = match separator {
+        PathSeparator::Newline => b'\n',
+        PathSeparator::Nul => b'\0',
+    };
+    Ok(bytes
+        .split(|&b| b == delimiter)
+        .map(|entry| match separator {
+            PathSeparator::Newline => entry.strip_suffix(b"\r").unwrap_or(entry),
+            PathSeparator::Nul => entry,
+        })
+        .filter(|entry| !entry.is_empty())
+        .map(path_from_bytes)
+        .collect())
+}
+
+/// 字节转路径
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+/// 字节转路径（非 Unix 平台按 UTF-8 解析）
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
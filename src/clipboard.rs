@@ -0,0 +1,61 @@
+//! 剪贴板
+//!
+//! 本模块通过 OSC 52 转义序列把文本交给终端写入剪贴板，经 SSH 连接时也能写入本地剪贴板；
+//! 配置了剪贴板命令时同时将文本通过标准输入传给该命令。
+
+use anyhow::{Context, bail};
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// 复制文本，返回所用方式的描述
+pub(crate) fn copy(text: &str, command: Option<&str>) -> anyhow::Result<String> {
+    let mut out = io::stdout();
+    write!(out, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    out.flush()?;
+
+    let Some(command) = command else {
+        return Ok("OSC 52".to_string());
+    };
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Cannot run clipboard command `{command}`"))?;
+    // 写入失败（如命令不读标准输入就退出）时也要等待子进程，避免留下僵尸进程；
+    // 标准输入在等待前关闭，命令才能读到文件结尾
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(text.as_bytes()),
+        None => Ok(()),
+    };
+    let status = child.wait()?;
+    written.with_context(|| format!("Cannot write to clipboard command `{command}`"))?;
+    if !status.success() {
+        bail!("Clipboard command `{command}` failed ({status})");
+    }
+    Ok(format!("OSC 52 and `{command}`"))
+}
+
+/// 标准 Base64 编码（带填充）
+fn base64(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(TABLE[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
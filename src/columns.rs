@@ -0,0 +1,218 @@
+//! 列布局
+//!
+//! 本模块定义交互界面每行显示的列及其顺序、宽度与对齐方式，可在配置文件中设置：
+//!
+//! ```toml
+//! [[columns]]
+//! column = "size"
+//! width = 10
+//!
+//! [[columns]]
+//! column = "mtime"
+//! hidden = true
+//!
+//! [[columns]]
+//! column = "name"
+//! ```
+//!
+//! 名称列（缩进、展开图标与名称）必须出现且只出现一次；未指定宽度时占满剩余宽度。
+
+use anyhow::bail;
+use serde::Deserialize;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// 列类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) enum ColumnKind {
+    Index,       // 行号
+    Size,        // 大小
+    Disk,        // 磁盘占用
+    Percent,     // 占比
+    Bar,         // 占比条
+    Items,       // 子树条目数
+    Mtime,       // 修改时间
+    Owner,       // 所有者
+    Permissions, // 权限
+    Extension,   // 扩展名
+    Name,        // 名称（含缩进与展开图标）
+}
+
+impl ColumnKind {
+    /// 所有列的名称，用于提示
+    pub(crate) const NAMES: &str =
+        "index, size, disk, percent, bar, items, mtime, owner, permissions, extension, name";
+
+    /// 解析列名（含简写）
+    pub(crate) fn parse(name: &str) -> anyhow::Result<ColumnKind> {
+        Ok(match name.trim().to_ascii_lowercase().as_str() {
+            "index" | "idx" => ColumnKind::Index,
+            "size" => ColumnKind::Size,
+            "disk" | "du" => ColumnKind::Disk,
+            "percent" | "%" => ColumnKind::Percent,
+            "bar" => ColumnKind::Bar,
+            "items" | "count" => ColumnKind::Items,
+            "mtime" | "modified" => ColumnKind::Mtime,
+            "owner" => ColumnKind::Owner,
+            "permissions" | "perm" | "mode" => ColumnKind::Permissions,
+            "extension" | "ext" => ColumnKind::Extension,
+            "name" => ColumnKind::Name,
+            _ => bail!("Unknown column `{name}` (expected one of {})", Self::NAMES),
+        })
+    }
+
+    /// 默认宽度（`None` 为自适应）
+    fn default_width(self) -> Option<usize> {
+        match self {
+            ColumnKind::Index | ColumnKind::Size | ColumnKind::Disk | ColumnKind::Name => None,
+            ColumnKind::Percent => Some(6),
+            ColumnKind::Bar => Some(12),
+            ColumnKind::Items => Some(7),
+            ColumnKind::Mtime => Some(16),
+            ColumnKind::Owner => Some(8),
+            ColumnKind::Permissions => Some(10),
+            ColumnKind::Extension => Some(6),
+        }
+    }
+
+    /// 默认对齐方式：数值右对齐，其余左对齐
+    fn default_align(self) -> Align {
+        match self {
+            ColumnKind::Index
+            | ColumnKind::Size
+            | ColumnKind::Disk
+            | ColumnKind::Percent
+            | ColumnKind::Items => Align::Right,
+            _ => Align::Left,
+        }
+    }
+}
+
+impl TryFrom<String> for ColumnKind {
+    type Error = anyhow::Error;
+
+    fn try_from(name: String) -> anyhow::Result<Self> {
+        ColumnKind::parse(&name)
+    }
+}
+
+/// 对齐方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Align {
+    Left,
+    Right,
+}
+
+/// 列配置
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Column {
+    #[serde(rename = "column")]
+    pub(crate) kind: ColumnKind, // 列类型
+    #[serde(default)]
+    width: Option<usize>, // 宽度（字符），省略时使用默认宽度
+    #[serde(default)]
+    align: Option<Align>, // 对齐方式，省略时使用默认对齐
+    #[serde(default)]
+    pub(crate) hidden: bool, // 隐藏，可在运行时切换
+}
+
+impl Column {
+    /// 使用默认宽度与对齐方式的列
+    fn new(kind: ColumnKind, hidden: bool) -> Column {
+        Column {
+            kind,
+            width: None,
+            align: None,
+            hidden,
+        }
+    }
+
+    /// 宽度（`None` 为自适应）
+    pub(crate) fn width(&self) -> Option<usize> {
+        self.width.or(self.kind.default_width())
+    }
+
+    /// 对齐方式
+    pub(crate) fn align(&self) -> Align {
+        self.align.unwrap_or(self.kind.default_align())
+    }
+}
+
+/// 默认列布局：行号、大小、占比（隐藏）、占比条（隐藏）、名称
+pub(crate) fn default_columns() -> Vec<Column> {
+    vec![
+        Column::new(ColumnKind::Index, false),
+        Column::new(ColumnKind::Size, false),
+        Column::new(ColumnKind::Percent, true),
+        Column::new(ColumnKind::Bar, true),
+        Column::new(ColumnKind::Name, false),
+    ]
+}
+
+/// 校验列布局：列不能重复，名称列必须出现且不能隐藏
+pub(crate) fn validate_columns(columns: &[Column]) -> anyhow::Result<()> {
+    for (i, column) in columns.iter().enumerate() {
+        if columns[..i].iter().any(|c| c.kind == column.kind) {
+            bail!("Column `{:?}` appears more than once", column.kind);
+        }
+        if column.width() == Some(0) {
+            bail!("Column `{:?}` has zero width", column.kind);
+        }
+    }
+    match columns.iter().find(|c| c.kind == ColumnKind::Name) {
+        None => bail!("The column layout must include `name`"),
+        Some(column) if column.hidden => bail!("The `name` column cannot be hidden"),
+        Some(_) => Ok(()),
+    }
+}
+
+/// 切换列的显示；布局中没有该列时插入到名称列之前
+pub(crate) fn toggle_column(columns: &mut Vec<Column>, kind: ColumnKind) -> anyhow::Result<bool> {
+    if kind == ColumnKind::Name {
+        bail!("The `name` column cannot be hidden");
+    }
+    match columns.iter_mut().find(|c| c.kind == kind) {
+        Some(column) => column.hidden = !column.hidden,
+        None => {
+            let name = columns
+                .iter()
+                .position(|c| c.kind == ColumnKind::Name)
+                .unwrap_or(columns.len());
+            columns.insert(name, Column::new(kind, false));
+        }
+    }
+    Ok(columns.iter().any(|c| c.kind == kind && !c.hidden))
+}
+
+/// 按显示宽度填充或截断文本，截断时以省略号结尾
+///
+/// 宽度按终端单元格计算，中日韩文字等宽字符占两格。
+pub(crate) fn fit(text: &str, width: usize, align: Align) -> String {
+    let len = text.width();
+    if len > width {
+        if width == 0 {
+            return String::new();
+        }
+        // 留出省略号的一格；放不下的宽字符以空格补齐
+        let mut truncated = String::new();
+        let mut used = 0;
+        for ch in text.chars() {
+            let cells = ch.width().unwrap_or(0);
+            if used + cells > width - 1 {
+                break;
+            }
+            truncated.push(ch);
+            used += cells;
+        }
+        truncated.push('…');
+        truncated.push_str(&" ".repeat(width - 1 - used));
+        return truncated;
+    }
+    let padding = " ".repeat(width - len);
+    match align {
+        Align::Left => format!("{text}{padding}"),
+        Align::Right => format!("{padding}{text}"),
+    }
+}
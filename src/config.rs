@@ -0,0 +1,104 @@
+//! 配置文件
+//!
+//! 本模块读取 `fswhy.toml`（或 `--config` / `FSWHY_CONFIG` 指定的文件），提供主题以外的可选设置。
+//!
+//! ```toml
+//! clipboard = "xclip -selection clipboard"
+//!
+//! [[commands]]
+//! key = "z"
+//! cmd = "tar czf {path}.tgz {path}"
+//! rescan = true
+//! ```
+
+use crate::columns::{Column, default_columns, validate_columns};
+use anyhow::{Context, bail};
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// 默认配置文件名（当前目录）
+const DEFAULT_CONFIG: &str = "fswhy.toml";
+
+/// 不能绑定自定义命令的按键：移动光标、展开折叠、计数前缀与退出
+const RESERVED_KEYS: &str = "0123456789jJkKhlgGqQ";
+
+/// 配置
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    #[serde(default)]
+    pub(crate) clipboard: Option<String>, // 剪贴板命令，从标准输入读取文本
+    #[serde(default)]
+    pub(crate) commands: Vec<CustomCommand>, // 绑定到按键的自定义命令
+    #[serde(default = "default_columns")]
+    pub(crate) columns: Vec<Column>, // 列布局，见 [`crate::columns`]
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            clipboard: None,
+            commands: Vec::new(),
+            columns: default_columns(),
+        }
+    }
+}
+
+/// 自定义命令
+///
+/// `cmd` 由 `sh -c` 运行，其中的 `{path}`、`{name}`、`{parent}`、`{size}` 替换为光标处条目的信息。
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CustomCommand {
+    pub(crate) key: char,   // 按键，优先于内置按键
+    pub(crate) cmd: String, // 命令模板
+    #[serde(default)]
+    pub(crate) rescan: bool, // 运行后重新扫描条目所在目录
+}
+
+impl Config {
+    /// 从文件加载配置
+    pub(crate) fn load_from_file(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let config: Config = toml::from_str(&text)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 校验配置：自定义命令的按键不能重复，列布局有效
+    fn validate(&self) -> anyhow::Result<()> {
+        validate_columns(&self.columns)?;
+        for (i, command) in self.commands.iter().enumerate() {
+            if command.cmd.trim().is_empty() {
+                bail!("Command for key `{}` is empty", command.key);
+            }
+            if RESERVED_KEYS.contains(command.key) {
+                bail!(
+                    "Key `{}` is reserved for navigation, count prefixes or quitting",
+                    command.key
+                );
+            }
+            if self.commands[..i].iter().any(|c| c.key == command.key) {
+                bail!("Key `{}` is bound to more than one command", command.key);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 加载配置：指定路径时必须加载成功，否则依次尝试 `FSWHY_CONFIG` 与 `./fswhy.toml`
+///
+/// 未找到配置文件时使用默认配置；文件存在但无效时报错。
+pub(crate) fn load_config(path: Option<&Path>) -> anyhow::Result<Config> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match env::var_os("FSWHY_CONFIG") {
+            Some(path) => path.into(),
+            None if Path::new(DEFAULT_CONFIG).exists() => DEFAULT_CONFIG.into(),
+            None => return Ok(Config::default()),
+        },
+    };
+    Config::load_from_file(&path).with_context(|| format!("Cannot load config {}", path.display()))
+}
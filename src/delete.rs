@@ -0,0 +1,66 @@
+//! 删除条目
+//!
+//! 本模块按扫描树在磁盘上递归删除文件与目录。单个条目出错时跳过并继续删除其余条目，逐条记录失败原因。
+
+use crate::model::{Node, NodeKind::Directory, device_of};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 删除失败的条目
+pub(crate) struct Failure {
+    pub path: PathBuf,    // 条目路径
+    pub error: io::Error, // 失败原因
+}
+
+/// 删除节点对应的条目，目录按扫描树递归删除，符号链接只删除链接本身
+///
+/// 只删除扫描树中存在的条目：被 `--exclude` 排除或未在路径列表中给出的条目保留在磁盘上，
+/// 其所在目录因非空而删除失败。不跨越文件系统边界，挂载点下的内容不会被删除。
+/// 返回是否完全删除；失败的条目追加到 `failures`。
+pub(crate) fn remove_tree(node: &Node, failures: &mut Vec<Failure>) -> bool {
+    let device = match fs::symlink_metadata(node.path()) {
+        Ok(meta) => device_of(&meta),
+        Err(e) => return record(failures, node.path(), Err(e)),
+    };
+    remove_node(node, device, failures)
+}
+
+/// 递归删除节点，`device` 为被删除条目所在的设备
+fn remove_node(node: &Node, device: Option<u64>, failures: &mut Vec<Failure>) -> bool {
+    let path = node.path();
+    let meta = match fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(e) => return record(failures, path, Err(e)),
+    };
+    if !meta.is_dir() {
+        return record(failures, path, fs::remove_file(path));
+    }
+    if device_of(&meta) != device {
+        let error = io::Error::other("on another filesystem, not removed");
+        return record(failures, path, Err(error));
+    }
+
+    let mut complete = true;
+    if let Directory(prop) = node.kind() {
+        for child in prop.children() {
+            complete &= remove_node(child, device, failures);
+        }
+    }
+    // 子项未删干净时目录必然删除失败，不再重复报告
+    complete && record(failures, path, fs::remove_dir(path))
+}
+
+/// 记录失败，返回是否成功
+fn record(failures: &mut Vec<Failure>, path: &Path, result: io::Result<()>) -> bool {
+    match result {
+        Ok(()) => true,
+        Err(error) => {
+            failures.push(Failure {
+                path: path.to_path_buf(),
+                error,
+            });
+            false
+        }
+    }
+}
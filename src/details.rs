@@ -0,0 +1,212 @@
+//! 条目详情
+//!
+//! 本模块为详情面板收集选中条目的信息：完整路径、大小与磁盘占用、占父目录和根的比例、
+//! 子项统计、最大子项，以及从文件系统读取的时间戳、所有者和权限。
+
+use crate::model::{Node, NodeKind::*};
+use crate::size::SizeFormat;
+use crate::time::format_local_time;
+use std::fs::Metadata;
+use std::time::SystemTime;
+
+/// 详情面板的行数（含标题行）
+pub(crate) const DETAILS_LINES: usize = 7;
+
+/// 收集条目详情，返回 (标签, 内容) 列表，共 `DETAILS_LINES - 1` 行
+pub(crate) fn describe(
+    root: &Node,
+    node: &Node,
+    format: SizeFormat,
+) -> Vec<(&'static str, String)> {
+    let path = if node.is_synthetic() {
+        node.root_label()
+    } else {
+        std::path::absolute(node.path())
+            .unwrap_or_else(|_| node.path().to_path_buf())
+            .display()
+            .to_string()
+    };
+
+    let share = |total: u64| {
+        if total == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.1}%", node.size() as f64 * 100.0 / total as f64)
+        }
+    };
+    let of_parent = root
+        .parent_of(node.path())
+        .map_or("-".to_string(), |p| share(p.size()));
+
+    let contents = match node.kind() {
+        Directory(prop) => {
+            let (dirs, files) = node.counts();
+            let largest =
+                prop.children()
+                    .iter()
+                    .max_by_key(|c| c.size())
+                    .map_or("-".to_string(), |c| {
+                        let name = c.path().file_name().unwrap_or(c.path().as_os_str());
+                        format!("{} ({})", name.to_string_lossy(), format.format(c.size()))
+                    });
+            format!("{dirs} dirs, {files} files; largest: {largest}")
+        }
+        File => "file".to_string(),
+    };
+
+    let (times, owner) = if node.is_synthetic() {
+        ("-".to_string(), "-".to_string())
+    } else {
+        match std::fs::metadata(node.path()) {
+            Ok(meta) => (
+                times(&meta),
+                format!("{}  {}", owner(&meta), permissions(&meta)),
+            ),
+            Err(e) => (e.to_string(), "-".to_string()),
+        }
+    };
+
+    vec![
+        ("Path", path),
+        (
+            "Size",
+            format!(
+                "{} apparent, {} on disk",
+                format.format(node.size()),
+                format.format(node.disk_size())
+            ),
+        ),
+        (
+            "Share",
+            format!("{of_parent} of parent, {} of root", share(root.size())),
+        ),
+        ("Contents", contents),
+        ("Times", times),
+        ("Owner", owner),
+    ]
+}
+
+/// 修改、访问、创建时间
+fn times(meta: &Metadata) -> String {
+    let format =
+        |time: std::io::Result<SystemTime>| time.map_or("-".to_string(), format_local_time);
+    format!(
+        "modified {}  accessed {}  created {}",
+        format(meta.modified()),
+        format(meta.accessed()),
+        format(meta.created())
+    )
+}
+
+/// 所有者与所属组（`user:group`，无法解析名称时为数字 ID）
+#[cfg(unix)]
+fn owner(meta: &Metadata) -> String {
+    use std::os::unix::fs::MetadataExt;
+    format!("{}:{}", user(meta), group_name(meta.gid()))
+}
+
+/// 所有者（非 Unix 平台不支持）
+#[cfg(not(unix))]
+fn owner(_meta: &Metadata) -> String {
+    "-".to_string()
+}
+
+/// 所有者用户名（无法解析时为数字 ID）
+#[cfg(unix)]
+pub(crate) fn user(meta: &Metadata) -> String {
+    use std::os::unix::fs::MetadataExt;
+    user_name(meta.uid())
+}
+
+/// 所有者用户名（非 Unix 平台不支持）
+#[cfg(not(unix))]
+pub(crate) fn user(_meta: &Metadata) -> String {
+    "-".to_string()
+}
+
+/// 用户名
+#[cfg(unix)]
+fn user_name(uid: u32) -> String {
+    let mut buf = vec![0 as libc::c_char; 4096];
+    // SAFETY: getpwuid_r 只写入调用方提供的 passwd 与缓冲区，成功时 pw_name 指向缓冲区内的字符串
+    unsafe {
+        let mut pwd: libc::passwd = std::mem::zeroed();
+        let mut result = std::ptr::null_mut();
+        if libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) == 0
+            && !result.is_null()
+        {
+            return std::ffi::CStr::from_ptr(pwd.pw_name)
+                .to_string_lossy()
+                .into_owned();
+        }
+    }
+    uid.to_string()
+}
+
+/// 组名
+#[cfg(unix)]
+fn group_name(gid: u32) -> String {
+    let mut buf = vec![0 as libc::c_char; 4096];
+    // SAFETY: getgrgid_r 只写入调用方提供的 group 与缓冲区，成功时 gr_name 指向缓冲区内的字符串
+    unsafe {
+        let mut grp: libc::group = std::mem::zeroed();
+        let mut result = std::ptr::null_mut();
+        if libc::getgrgid_r(gid, &mut grp, buf.as_mut_ptr(), buf.len(), &mut result) == 0
+            && !result.is_null()
+        {
+            return std::ffi::CStr::from_ptr(grp.gr_name)
+                .to_string_lossy()
+                .into_owned();
+        }
+    }
+    gid.to_string()
+}
+
+/// 权限，形如 `drwxr-xr-x (0755)`
+#[cfg(unix)]
+fn permissions(meta: &Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = meta.permissions().mode();
+    format!("{} ({:04o})", mode_string(meta), mode & 0o7777)
+}
+
+/// 权限字符串，形如 `drwxr-xr-x`
+#[cfg(unix)]
+pub(crate) fn mode_string(meta: &Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = meta.permissions().mode();
+    let mut text = String::with_capacity(10);
+    text.push(if meta.is_dir() { 'd' } else { '-' });
+    for (shift, special, set, unset) in [
+        (6, 0o4000, 's', 'S'),
+        (3, 0o2000, 's', 'S'),
+        (0, 0o1000, 't', 'T'),
+    ] {
+        let bits = mode >> shift;
+        text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        text.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => set,
+            (false, true) => unset,
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    text
+}
+
+/// 权限（非 Unix 平台只有只读标志）
+#[cfg(not(unix))]
+fn permissions(meta: &Metadata) -> String {
+    mode_string(meta)
+}
+
+/// 权限字符串（非 Unix 平台只有只读标志）
+#[cfg(not(unix))]
+pub(crate) fn mode_string(meta: &Metadata) -> String {
+    if meta.permissions().readonly() {
+        "read-only".to_string()
+    } else {
+        "read-write".to_string()
+    }
+}
@@ -0,0 +1,193 @@
+//! 导出
+//!
+//! 本模块将 [`Node`] 树导出为其他工具可读取的格式，例如可直接交给 inferno / flamegraph.pl 的折叠栈（folded stacks），
+//! 以及 node_exporter textfile collector 可采集的 Prometheus 指标。
+
+use crate::model::{Node, NodeKind::*, ScanStats};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// 以折叠栈格式导出文件树
+///
+/// 每个文件输出一行 `root;dir;file <bytes>`，目录本身不单独输出。
+/// 名称中的 `;` 与换行会被替换为 `_`，以免破坏栈的分隔。合成根不占栈帧，各扫描根各自作为栈底。
+pub fn write_folded(node: &Node, out: &mut impl Write) -> io::Result<()> {
+    let mut stack = if node.is_synthetic() {
+        Vec::new()
+    } else {
+        vec![frame_name(node, true)]
+    };
+    write_folded_recursive(node, &mut stack, out)
+}
+
+/// 递归输出叶子文件
+fn write_folded_recursive(
+    node: &Node,
+    stack: &mut Vec<String>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    match node.kind() {
+        Directory(prop) => {
+            for child in prop.children() {
+                stack.push(frame_name(child, node.is_synthetic()));
+                write_folded_recursive(child, stack, out)?;
+                stack.pop();
+            }
+            Ok(())
+        }
+        File => writeln!(out, "{} {}", stack.join(";"), node.size()),
+    }
+}
+
+/// 栈帧名称（根节点使用完整路径）
+fn frame_name(node: &Node, is_root: bool) -> String {
+    let name = if is_root {
+        node.path().display().to_string()
+    } else {
+        node.path()
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    };
+    name.replace([';', '\n', '\r'], "_")
+}
+
+/// 以 Prometheus 文本格式导出目录指标
+///
+/// 输出深度不超过 `max_depth`（根为 0）的每个目录的大小与文件数，以及本次扫描的耗时、错误数和完成时间。
+pub fn write_prometheus(
+    node: &Node,
+    stats: &ScanStats,
+    max_depth: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let mut dirs = Vec::new();
+    if let Directory(prop) = node.kind()
+        && node.is_synthetic()
+    {
+        // 合成根本身没有路径，只导出各扫描根
+        for root in prop.children() {
+            collect_dirs(root, 0, max_depth, &mut dirs);
+        }
+    } else {
+        collect_dirs(node, 0, max_depth, &mut dirs);
+    }
+    let root = label_value(Path::new(&node.root_label()));
+
+    writeln!(
+        out,
+        "# HELP fswhy_dir_bytes Total size in bytes of files under the directory."
+    )?;
+    writeln!(out, "# TYPE fswhy_dir_bytes gauge")?;
+    for dir in &dirs {
+        writeln!(
+            out,
+            "fswhy_dir_bytes{{path=\"{}\"}} {}",
+            label_value(dir.path()),
+            dir.size()
+        )?;
+    }
+
+    writeln!(
+        out,
+        "# HELP fswhy_dir_files Number of files under the directory, recursively."
+    )?;
+    writeln!(out, "# TYPE fswhy_dir_files gauge")?;
+    for dir in &dirs {
+        writeln!(
+            out,
+            "fswhy_dir_files{{path=\"{}\"}} {}",
+            label_value(dir.path()),
+            dir.counts().1
+        )?;
+    }
+
+    writeln!(
+        out,
+        "# HELP fswhy_scan_duration_seconds Time spent scanning the root."
+    )?;
+    writeln!(out, "# TYPE fswhy_scan_duration_seconds gauge")?;
+    writeln!(
+        out,
+        "fswhy_scan_duration_seconds{{root=\"{root}\"}} {:.3}",
+        stats.duration.as_secs_f64()
+    )?;
+
+    writeln!(
+        out,
+        "# HELP fswhy_scan_errors Entries skipped during the scan because of errors."
+    )?;
+    writeln!(out, "# TYPE fswhy_scan_errors gauge")?;
+    writeln!(out, "fswhy_scan_errors{{root=\"{root}\"}} {}", stats.errors)?;
+
+    writeln!(
+        out,
+        "# HELP fswhy_scan_items Entries visited during the scan."
+    )?;
+    writeln!(out, "# TYPE fswhy_scan_items gauge")?;
+    writeln!(out, "fswhy_scan_items{{root=\"{root}\"}} {}", stats.items)?;
+
+    let finished = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    writeln!(
+        out,
+        "# HELP fswhy_scan_timestamp_seconds Unix time when the scan finished."
+    )?;
+    writeln!(out, "# TYPE fswhy_scan_timestamp_seconds gauge")?;
+    writeln!(
+        out,
+        "fswhy_scan_timestamp_seconds{{root=\"{root}\"}} {}",
+        finished.as_secs()
+    )
+}
+
+/// 原子写入 Prometheus 指标文件
+///
+/// 先写入同目录下的临时文件再重命名，避免 textfile collector 读到写了一半的文件。
+pub fn write_prometheus_file(
+    node: &Node,
+    stats: &ScanStats,
+    max_depth: usize,
+    target: &Path,
+) -> io::Result<()> {
+    let mut tmp = target.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    let tmp = Path::new(&tmp);
+
+    let result = (|| {
+        let mut out = io::BufWriter::new(fs::File::create(tmp)?);
+        write_prometheus(node, stats, max_depth, &mut out)?;
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(tmp, target)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(tmp);
+    }
+    result
+}
+
+/// 收集不超过指定深度的目录
+fn collect_dirs<'a>(node: &'a Node, depth: usize, max_depth: usize, dirs: &mut Vec<&'a Node>) {
+    let Directory(prop) = node.kind() else {
+        return;
+    };
+    dirs.push(node);
+    if depth < max_depth {
+        for child in prop.children() {
+            collect_dirs(child, depth + 1, max_depth, dirs);
+        }
+    }
+}
+
+/// 转义标签值（反斜杠、双引号、换行）
+fn label_value(path: &Path) -> String {
+    path.display()
+        .to_string()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
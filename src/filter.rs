@@ -0,0 +1,208 @@
+//! 视图过滤
+//!
+//! 本模块解析过滤表达式为 [`Filter`]，并计算过滤后可见的节点及其按匹配项重新统计的大小。
+//!
+//! 表达式由空格分隔的条件组成，所有条件同时满足才算匹配：
+//!
+//! - `name:<模式>` 或不带前缀的词：名称匹配（子串、`*?[` glob 或 `re:` 正则）
+//! - `ext:log,txt`：扩展名（不区分大小写）
+//! - `size:>10M`、`size:<1K`：大小（省略比较符时为 `>`）
+//! - `age:>30d`、`age:<2h`：距修改时间的时长，单位 `s`/`m`/`h`/`d`/`w`/`y`
+//! - `kind:file`、`kind:dir`：条目类型（默认只匹配文件）
+//!
+//! 匹配的文件连同其祖先目录可见；`kind:dir` 时匹配的目录连同整棵子树可见。
+
+use crate::check::parse_size;
+use crate::model::{Node, NodeKind::*};
+use crate::pattern::Matcher;
+use anyhow::{Context, bail};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// 过滤条件
+enum Condition {
+    Name(Matcher),       // 名称
+    Ext(Vec<String>),    // 扩展名（小写）
+    Size(bool, u64),     // 大小，`true` 为大于
+    Age(bool, Duration), // 修改时长，`true` 为早于
+}
+
+/// 过滤表达式
+pub(crate) struct Filter {
+    pub text: String,           // 原始表达式
+    conditions: Vec<Condition>, // 条件
+    dirs: bool,                 // 匹配目录而不是文件
+}
+
+impl Filter {
+    /// 解析过滤表达式
+    pub fn parse(text: &str) -> anyhow::Result<Filter> {
+        let mut conditions = Vec::new();
+        let mut dirs = false;
+        for token in text.split_whitespace() {
+            let (key, value) = match token.split_once(':') {
+                Some((key @ ("name" | "ext" | "size" | "age" | "kind"), value)) => (key, value),
+                _ => ("name", token),
+            };
+            if value.is_empty() {
+                bail!("Missing value for `{key}:`");
+            }
+            let condition = match key {
+                "name" => Condition::Name(Matcher::parse(value)?),
+                "ext" => Condition::Ext(
+                    value
+                        .split(',')
+                        .map(|ext| ext.trim_start_matches('.').to_lowercase())
+                        .collect(),
+                ),
+                "size" => {
+                    let (greater, value) = comparison(value);
+                    Condition::Size(greater, parse_size(value)?)
+                }
+                "age" => {
+                    let (older, value) = comparison(value);
+                    Condition::Age(older, parse_age(value)?)
+                }
+                _ => {
+                    dirs = match value {
+                        "file" | "f" => false,
+                        "dir" | "d" => true,
+                        _ => bail!("Unknown kind `{value}` (expected file or dir)"),
+                    };
+                    continue;
+                }
+            };
+            conditions.push(condition);
+        }
+        Ok(Filter {
+            text: text.trim().to_string(),
+            conditions,
+            dirs,
+        })
+    }
+
+    /// 判断单个节点是否匹配
+    fn matches(&self, node: &Node, now: SystemTime) -> bool {
+        if node.kind().is_dir() != self.dirs || node.is_synthetic() {
+            return false;
+        }
+        let name = node
+            .path()
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        self.conditions.iter().all(|condition| match condition {
+            Condition::Name(matcher) => matcher.is_match(&name),
+            Condition::Ext(exts) => node
+                .path()
+                .extension()
+                .is_some_and(|ext| exts.contains(&ext.to_string_lossy().to_lowercase())),
+            Condition::Size(true, limit) => node.size() > *limit,
+            Condition::Size(false, limit) => node.size() < *limit,
+            Condition::Age(older, limit) => node
+                .modified()
+                .and_then(|m| now.duration_since(m).ok())
+                .is_some_and(|age| if *older { age > *limit } else { age < *limit }),
+        })
+    }
+
+    /// 计算过滤后可见的节点，值为只统计匹配项的大小
+    ///
+    /// 根节点总是可见。
+    pub fn visible(&self, root: &Node) -> HashMap<PathBuf, u64> {
+        let mut visible = HashMap::new();
+        let now = SystemTime::now();
+        let size = self.collect(root, now, &mut visible).unwrap_or(0);
+        visible.insert(root.path().to_path_buf(), size);
+        visible
+    }
+
+    /// 递归收集可见节点，返回节点的过滤后大小（不可见时为 `None`）
+    fn collect(
+        &self,
+        node: &Node,
+        now: SystemTime,
+        visible: &mut HashMap<PathBuf, u64>,
+    ) -> Option<u64> {
+        if self.matches(node, now) {
+            insert_subtree(node, visible);
+            return Some(node.size());
+        }
+        let Directory(prop) = node.kind() else {
+            return None;
+        };
+        let mut size = None;
+        for child in prop.children() {
+            if let Some(child_size) = self.collect(child, now, visible) {
+                size = Some(size.unwrap_or(0) + child_size);
+            }
+        }
+        if let Some(size) = size {
+            visible.insert(node.path().to_path_buf(), size);
+        }
+        size
+    }
+}
+
+/// 将整棵子树按原大小标记为可见
+fn insert_subtree(node: &Node, visible: &mut HashMap<PathBuf, u64>) {
+    visible.insert(node.path().to_path_buf(), node.size());
+    if let Directory(prop) = node.kind() {
+        for child in prop.children() {
+            insert_subtree(child, visible);
+        }
+    }
+}
+
+/// 拆出比较符，返回是否为大于
+fn comparison(value: &str) -> (bool, &str) {
+    match value.strip_prefix('<') {
+        Some(rest) => (false, rest),
+        None => (true, value.strip_prefix('>').unwrap_or(value)),
+    }
+}
+
+/// 解析时长（`90s`、`30d`、`1y`，无单位时为天）
+fn parse_age(text: &str) -> anyhow::Result<Duration> {
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .with_context(|| format!("Invalid age `{text}`"))?;
+    let seconds: f64 = match unit {
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        "" | "d" => 86400.0,
+        "w" => 7.0 * 86400.0,
+        "y" => 365.0 * 86400.0,
+        _ => bail!("Unknown age unit in `{text}` (expected s, m, h, d, w or y)"),
+    };
+    let age = Duration::try_from_secs_f64(number * seconds)
+        .ok()
+        .with_context(|| format!("Age `{text}` is too large"))?;
+    Ok(age)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_age_units() {
+        assert_eq!(parse_age("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_age("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_age("1.5").unwrap(), Duration::from_secs(129600));
+        assert!(parse_age("3x").is_err());
+    }
+
+    #[test]
+    fn parse_age_rejects_overflow() {
+        assert!(parse_age("1000000000000y").is_err());
+        assert!(parse_age("999999999999999d").is_err());
+        assert!(Filter::parse("age:>1000000000000y").is_err());
+    }
+}
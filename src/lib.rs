@@ -1,41 +1,121 @@
 //! 应用主入口与事件循环
 
-use crate::model::Node;
-use crate::theme::load_theme_from_env_or_default;
-use crate::ui_state::{StatusMessage, UiState};
+use crate::config::load_config;
+use crate::model::{Node, ScanOptions, ScanStats};
+use crate::theme::load_theme;
+use crate::ui_state::{Action, StatusMessage, UiState};
+use anyhow::bail;
 use std::path::PathBuf;
 
+pub mod check;
+pub mod cli;
+mod clipboard;
+mod columns;
+mod config;
+mod delete;
+mod details;
+pub mod export;
+mod filter;
 pub mod model;
+mod pattern;
+pub mod report;
+mod size;
+mod spawn;
 mod theme;
+mod time;
+mod trash;
 mod ui;
 mod ui_state;
 
+pub use size::{SizeFormat, SizeUnits};
+pub use ui_state::SortMode;
+
+/// 交互界面选项
+#[derive(Clone, Debug, Default)]
+pub struct UiOptions {
+    pub theme_path: Option<PathBuf>, // 主题文件，未指定时使用 FSWHY_THEME 或 ./theme.toml
+    pub config_path: Option<PathBuf>, // 配置文件，未指定时使用 FSWHY_CONFIG 或 ./fswhy.toml
+    pub sort_mode: SortMode,         // 初始排序模式
+    pub dry_run: bool,               // 删除时只报告，不修改磁盘
+    pub size_format: SizeFormat,     // 大小格式
+}
+
 /// 应用容器，持有文件树根节点
 pub struct App {
     pub node: Node,
+    pub stats: ScanStats,
+    pub scan_options: Option<ScanOptions>, // 扫描选项，由路径列表构建时为 None
 }
 
 impl App {
     /// 扫描指定路径并初始化应用
-    pub fn new(path: PathBuf) -> anyhow::Result<Self> {
-        let root = Node::scan(path)?;
-        Ok(Self { node: root })
+    ///
+    /// 传入多个路径时依次扫描，并以合成根节点（见 [`Node::combine`]）汇总，大小与占比按所有根合计。
+    pub fn new(paths: Vec<PathBuf>, options: &ScanOptions) -> anyhow::Result<Self> {
+        if paths.is_empty() {
+            bail!("No path to scan");
+        }
+        // 互相包含的根会被重复计算
+        for (i, a) in paths.iter().enumerate() {
+            for b in &paths[i + 1..] {
+                let (ca, cb) = (a.canonicalize()?, b.canonicalize()?);
+                if ca.starts_with(&cb) || cb.starts_with(&ca) {
+                    bail!("Overlapping roots: {} and {}", a.display(), b.display());
+                }
+            }
+        }
+
+        let mut roots = Vec::with_capacity(paths.len());
+        let mut stats = ScanStats::default();
+        for path in paths {
+            let (root, root_stats) = Node::scan_with_stats(path, options)?;
+            stats.merge(&root_stats);
+            roots.push(root);
+        }
+        let node = if roots.len() == 1 {
+            roots.remove(0)
+        } else {
+            Node::combine(roots)
+        };
+        Ok(Self {
+            node,
+            stats,
+            scan_options: Some(options.clone()),
+        })
+    }
+
+    /// 由路径列表初始化应用，只统计列出的路径（见 [`Node::from_paths`]）
+    pub fn from_path_list(paths: Vec<PathBuf>, size_format: SizeFormat) -> anyhow::Result<Self> {
+        let (node, stats) = Node::from_paths(paths, size_format)?;
+        Ok(Self {
+            node,
+            stats,
+            scan_options: None,
+        })
     }
 
-    /// 创建UI状态
-    fn create_ui_state(&self) -> UiState<'_> {
-        let theme = load_theme_from_env_or_default();
-        UiState::new(&self.node, theme)
+    /// 创建UI状态，树交由界面持有以便就地修改
+    fn into_ui_state(self, options: &UiOptions) -> anyhow::Result<UiState> {
+        let theme = load_theme(options.theme_path.as_deref())?;
+        let mut state = UiState::new(self.node, theme, options.sort_mode);
+        state.config = load_config(options.config_path.as_deref())?;
+        state.dry_run = options.dry_run;
+        state.size_format = options.size_format;
+        state.scan_options = self.scan_options;
+        Ok(state)
     }
 
     /// 主循环：渲染 → 输入 → 更新
-    pub fn run(&self) -> anyhow::Result<()> {
-        let _raw_mode_guard = ui::RawModeGuard::new()?;
-        let mut state = self.create_ui_state();
+    pub fn run(self, options: &UiOptions) -> anyhow::Result<()> {
+        let mut state = self.into_ui_state(options)?;
+        let mut raw_mode_guard = Some(ui::RawModeGuard::new()?);
+        let mut screen_guard = Some(ui::AlternateScreenGuard::new()?);
+        let mut renderer = ui::Renderer::new();
+        state.update(ui::resize_action()?)?;
         loop {
-            ui::render(&state);
+            renderer.render(&state);
 
-            let action = match ui::get_input() {
+            let action = match ui::get_input(state.prompt.is_some(), &state.config.commands) {
                 Ok(action) => action,
                 Err(e) => {
                     state.status = Some(StatusMessage {
@@ -46,9 +126,12 @@ impl App {
                 }
             };
 
+            if let Action::Resize(..) = action {
+                renderer.invalidate();
+            }
             match state.update(action) {
                 Ok(false) => break Ok(()),
-                Ok(true) => continue,
+                Ok(true) => {}
                 Err(e) => {
                     state.status = Some(StatusMessage {
                         text: e.to_string(),
@@ -56,6 +139,18 @@ impl App {
                     });
                 }
             }
+
+            // 外部程序需要正常终端：先离开备用屏幕和 raw mode，退出并重新扫描后恢复，整屏重绘
+            if let Some(spawn) = state.spawn.take() {
+                drop(screen_guard.take());
+                drop(raw_mode_guard.take());
+                let result = spawn.run();
+                state.finish_spawn(&spawn, result);
+                raw_mode_guard = Some(ui::RawModeGuard::new()?);
+                screen_guard = Some(ui::AlternateScreenGuard::new()?);
+                renderer.invalidate();
+                state.update(ui::resize_action()?)?;
+            }
         }
     }
 }
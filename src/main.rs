@@ -1,14 +1,102 @@
 use anyhow::Result;
 use fswhy::App;
+use fswhy::check::{check, load_rules, write_report};
+use fswhy::cli::{self, Command, Output, Parsed};
+use fswhy::export::{write_folded, write_prometheus, write_prometheus_file};
+use fswhy::report::{write_markdown, write_tree};
 use std::env;
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Prometheus 导出的默认目录深度
+const DEFAULT_PROMETHEUS_DEPTH: usize = 1;
+
 fn main() -> Result<()> {
-    let root_path: PathBuf = env::args()
-        .nth(1)
-        .map(PathBuf::from)
-        .unwrap_or(env::current_dir()?);
+    let cli = match cli::parse(env::args().skip(1)) {
+        Ok(Parsed::Run(cli)) => *cli,
+        Ok(Parsed::Help) => {
+            print!("{}", cli::HELP);
+            return Ok(());
+        }
+        Ok(Parsed::Version) => {
+            println!("fswhy {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\nFor more information, try '--help'.");
+            std::process::exit(2);
+        }
+    };
+    // 先加载规则，避免规则文件有误时白白扫描
+    let rules = match &cli.command {
+        Command::Check { rules } => load_rules(rules)?,
+        Command::Interactive => Vec::new(),
+    };
+
+    let app = match cli.stdin {
+        Some(separator) => App::from_path_list(
+            cli::read_path_list(io::stdin().lock(), separator)?,
+            cli.ui.size_format,
+        )?,
+        None if cli.paths.is_empty() => App::new(vec![env::current_dir()?], &cli.scan)?,
+        None => App::new(cli.paths, &cli.scan)?,
+    };
+
+    if let Command::Check { .. } = cli.command {
+        let violations = check(&app.node, &rules);
+        let mut out = io::stdout().lock();
+        write_report(
+            &app.node,
+            &rules,
+            &violations,
+            cli.report.size_format,
+            &mut out,
+        )?;
+        out.flush()?;
+        if !violations.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
-    let app = App::new(root_path)?;
-    app.run()?;
+    if cli.outputs.is_empty() {
+        return app.run(&cli.ui);
+    }
+    // 依次写出所有非交互输出，不进入交互界面
+    for output in &cli.outputs {
+        match output {
+            Output::Folded(target) => {
+                let mut out: Box<dyn Write> = if target == "-" {
+                    Box::new(io::stdout().lock())
+                } else {
+                    Box::new(File::create(target)?)
+                };
+                let mut out = BufWriter::new(&mut out);
+                write_folded(&app.node, &mut out)?;
+                out.flush()?;
+            }
+            Output::Prometheus(target) => {
+                let depth = cli.report.max_depth.unwrap_or(DEFAULT_PROMETHEUS_DEPTH);
+                if target == "-" {
+                    let mut out = BufWriter::new(io::stdout().lock());
+                    write_prometheus(&app.node, &app.stats, depth, &mut out)?;
+                    out.flush()?;
+                } else {
+                    write_prometheus_file(&app.node, &app.stats, depth, Path::new(target))?;
+                }
+            }
+            Output::Tree => {
+                let mut out = BufWriter::new(io::stdout().lock());
+                write_tree(&app.node, &cli.report, &mut out)?;
+                out.flush()?;
+            }
+            Output::Markdown => {
+                let mut out = BufWriter::new(io::stdout().lock());
+                write_markdown(&app.node, &cli.report, &mut out)?;
+                out.flush()?;
+            }
+        }
+    }
     Ok(())
 }
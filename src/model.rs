@@ -3,15 +3,20 @@
 //! 本模块提供了 [`Node`] 结构体，用于递归表示文件和目录信息，并提供 [`Node::scan`] 方法从实际文件系统构建树形结构。
 
 use crate::model::NodeKind::*;
-use std::path::{Path, PathBuf};
+use crate::pattern::glob_match;
+use crate::size::SizeFormat;
+use anyhow::bail;
+use std::path::{Component, Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
 
 #[derive(PartialOrd, PartialEq, Debug)]
 pub struct Node {
     path: PathBuf,
     size: u64,
+    disk_size: u64,
     kind: NodeKind,
+    modified: Option<SystemTime>,
 }
 
 #[derive(PartialOrd, PartialEq, Debug)]
@@ -29,12 +34,89 @@ impl NodeKind {
 #[derive(PartialOrd, PartialEq, Debug)]
 pub struct DirProperty {
     children: Vec<Node>,
+    counts: (usize, usize), // 子树中的目录数与文件数（不含自身），随子节点变化更新
 }
 
 impl DirProperty {
+    fn new(children: Vec<Node>) -> DirProperty {
+        let mut prop = DirProperty {
+            children,
+            counts: (0, 0),
+        };
+        prop.recount();
+        prop
+    }
+
     pub fn children(&self) -> &[Node] {
         &self.children
     }
+
+    /// 由子节点重新计算子树中的目录数与文件数
+    fn recount(&mut self) {
+        self.counts = self
+            .children
+            .iter()
+            .fold((0, 0), |(dirs, files), child| match &child.kind {
+                Directory(prop) => (dirs + prop.counts.0 + 1, files + prop.counts.1),
+                File => (dirs, files + 1),
+            });
+    }
+}
+
+/// 扫描统计
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ScanStats {
+    pub items: usize,       // 扫描项数
+    pub errors: usize,      // 跳过的出错条目数
+    pub duration: Duration, // 扫描耗时
+}
+
+impl ScanStats {
+    /// 累加另一次扫描的统计
+    pub fn merge(&mut self, other: &ScanStats) {
+        self.items += other.items;
+        self.errors += other.errors;
+        self.duration += other.duration;
+    }
+}
+
+/// 扫描选项
+#[derive(Clone, Debug, Default)]
+pub struct ScanOptions {
+    pub exclude: Vec<String>,    // 排除的 glob，匹配名称或相对根的路径
+    pub one_file_system: bool,   // 不跨越文件系统边界
+    pub size_format: SizeFormat, // 进度信息中的大小格式
+}
+
+impl ScanOptions {
+    /// 判断条目是否被排除
+    fn is_excluded(&self, root: &Path, path: &Path) -> bool {
+        if self.exclude.is_empty() {
+            return false;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let relative = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+        self.exclude
+            .iter()
+            .any(|pattern| glob_match(pattern, &name) || glob_match(pattern, &relative))
+    }
+}
+
+/// 扫描过程中共享的上下文
+struct ScanContext<'o> {
+    items: AtomicUsize,
+    errors: AtomicUsize,
+    options: &'o ScanOptions,
+    root: PathBuf,
+    root_device: Option<u64>,
+    quiet: bool,
+}
+
+impl ScanContext<'_> {
+    /// 是否输出该深度的进度信息：只输出顶层条目，静默扫描时不输出
+    fn reports(&self, depth: usize) -> bool {
+        !self.quiet && depth <= 1
+    }
 }
 
 impl Node {
@@ -46,30 +128,350 @@ impl Node {
         self.size
     }
 
+    /// 占用的磁盘空间（按分配的块计算，目录含自身占用）
+    pub fn disk_size(&self) -> u64 {
+        self.disk_size
+    }
+
     pub fn kind(&self) -> &NodeKind {
         &self.kind
     }
 
+    /// 修改时间（无法获取时为 `None`）
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// 是否为合并多个扫描根的合成根节点（路径为空）
+    pub fn is_synthetic(&self) -> bool {
+        self.path.as_os_str().is_empty()
+    }
+
+    /// 根节点标签：完整路径，合成根为各扫描根路径列表
+    pub fn root_label(&self) -> String {
+        match &self.kind {
+            Directory(prop) if self.is_synthetic() => prop
+                .children
+                .iter()
+                .map(|c| c.path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => self.path.display().to_string(),
+        }
+    }
+
+    /// 将多个扫描根合并为一个合成根节点
+    ///
+    /// 合成根的路径为空，大小为各根之和，子节点保持传入顺序。
+    pub fn combine(roots: Vec<Node>) -> Node {
+        Node {
+            path: PathBuf::new(),
+            size: roots.iter().map(|r| r.size).sum(),
+            disk_size: roots.iter().map(|r| r.disk_size).sum(),
+            kind: Directory(DirProperty::new(roots)),
+            modified: None,
+        }
+    }
+
+    /// 子树中的目录数与文件数（不含自身），构建树时计算
+    pub fn counts(&self) -> (usize, usize) {
+        match &self.kind {
+            Directory(prop) => prop.counts,
+            File => (0, 0),
+        }
+    }
+
+    /// 按路径查找子树中的节点
+    pub fn find(&self, path: &Path) -> Option<&Node> {
+        if self.path == path {
+            return Some(self);
+        }
+        match &self.kind {
+            Directory(prop) => prop
+                .children
+                .iter()
+                .find(|c| path.starts_with(&c.path))
+                .and_then(|c| c.find(path)),
+            File => None,
+        }
+    }
+
+    /// 查找 `path` 的父节点（扫描根的父节点为合成根）
+    pub fn parent_of(&self, path: &Path) -> Option<&Node> {
+        let Directory(prop) = &self.kind else {
+            return None;
+        };
+        let child = prop.children.iter().find(|c| path.starts_with(&c.path))?;
+        if child.path == path {
+            Some(self)
+        } else {
+            child.parent_of(path)
+        }
+    }
+
+    /// 用重新扫描得到的节点替换树中同路径的节点，并更新祖先大小
+    ///
+    /// 返回被替换的旧节点；树中没有该路径时返回 `None`。
+    pub fn replace(&mut self, node: Node) -> Option<Node> {
+        if self.path == node.path {
+            return Some(std::mem::replace(self, node));
+        }
+        let Directory(prop) = &mut self.kind else {
+            return None;
+        };
+        let child = prop
+            .children
+            .iter_mut()
+            .find(|c| node.path.starts_with(&c.path))?;
+        let before = (child.size, child.disk_size);
+        let old = child.replace(node)?;
+        self.size = self.size - before.0 + child.size;
+        self.disk_size = self.disk_size - before.1 + child.disk_size;
+        prop.recount();
+        Some(old)
+    }
+
+    /// 从 `path` 子树中移除磁盘上已不存在的条目，并更新祖先大小
+    ///
+    /// 返回移除的（字节数, 磁盘占用）。用于删除后就地更新树，部分删除失败时保留仍存在的条目。
+    pub fn prune_missing(&mut self, path: &Path) -> (u64, u64) {
+        if self.path == path {
+            return self.prune_subtree();
+        }
+        let Directory(prop) = &mut self.kind else {
+            return (0, 0);
+        };
+        let Some(index) = prop.children.iter().position(|c| path.starts_with(&c.path)) else {
+            return (0, 0);
+        };
+        let child = &mut prop.children[index];
+        let removed = if child.path == path && std::fs::symlink_metadata(path).is_err() {
+            let child = prop.children.remove(index);
+            (child.size, child.disk_size)
+        } else {
+            child.prune_missing(path)
+        };
+        prop.recount();
+        self.size -= removed.0;
+        self.disk_size -= removed.1;
+        removed
+    }
+
+    /// 递归移除子节点中已不存在的条目，返回移除的（字节数, 磁盘占用）
+    fn prune_subtree(&mut self) -> (u64, u64) {
+        let Directory(prop) = &mut self.kind else {
+            return (0, 0);
+        };
+        let mut removed = (0, 0);
+        prop.children.retain_mut(|child| {
+            let missing = std::fs::symlink_metadata(&child.path).is_err();
+            let (size, disk_size) = if missing {
+                (child.size, child.disk_size)
+            } else {
+                child.prune_subtree()
+            };
+            removed.0 += size;
+            removed.1 += disk_size;
+            !missing
+        });
+        prop.recount();
+        self.size -= removed.0;
+        self.disk_size -= removed.1;
+        removed
+    }
+
     /// 递归扫描文件系统，构建节点树
     ///
     /// 此方法构建 [`Node`] 树。通过对子节点的大小求和来计算目录的总大小，并根据特定优先级对条目进行排序：
     /// 1. 目录优先于文件。
     /// 2. 同类型条目按路径字母顺序排序。
+    ///
     /// 扫描过程中会将进度信息显示到标准错误输出：
     /// - 每扫描 100 项显示一次进度
     /// - 顶层目录会显示详细统计信息
     ///
     /// # 错误
-    /// 如果路径不存在或权限不足以读取目录，则返回错误。
+    /// 如果根路径不存在或无法读取，则返回错误；其下无法访问的条目会被跳过并计入错误数，
+    /// 不会中止扫描。
     pub fn scan(path: PathBuf) -> anyhow::Result<Node> {
-        // 全局计数器，跨所有层级统计
-        static TOTAL_COUNT: AtomicUsize = AtomicUsize::new(0);
-        TOTAL_COUNT.store(0, Ordering::Relaxed);
+        Self::scan_with_stats(path, &ScanOptions::default()).map(|(node, _)| node)
+    }
 
-        eprintln!("Scanning {}...", path.display());
-        let result = Self::scan_with_progress(path, 0, &TOTAL_COUNT);
-        eprintln!();
-        result
+    /// 按选项扫描并返回统计信息
+    ///
+    /// 与 [`scan`](Self::scan) 相同，额外应用 [`ScanOptions`]，并返回扫描项数、跳过的错误数和耗时。
+    pub fn scan_with_stats(
+        path: PathBuf,
+        options: &ScanOptions,
+    ) -> anyhow::Result<(Node, ScanStats)> {
+        let root = path.clone();
+        Self::scan_under(path, root, options, false)
+    }
+
+    /// 重新扫描扫描根 `root` 下的子树
+    ///
+    /// 排除规则中的相对路径与文件系统边界仍相对原扫描根计算，与首次扫描的结果一致。
+    /// 交互界面处于全屏模式时调用，因此不输出进度信息。
+    pub(crate) fn rescan(
+        path: PathBuf,
+        root: &Path,
+        options: &ScanOptions,
+    ) -> anyhow::Result<Node> {
+        Self::scan_under(path, root.to_path_buf(), options, true).map(|(node, _)| node)
+    }
+
+    /// 扫描 `path`，排除规则与文件系统边界相对 `root` 计算，`quiet` 时不输出进度
+    fn scan_under(
+        path: PathBuf,
+        root: PathBuf,
+        options: &ScanOptions,
+        quiet: bool,
+    ) -> anyhow::Result<(Node, ScanStats)> {
+        // 计数器跨所有层级统计
+        let ctx = ScanContext {
+            items: AtomicUsize::new(0),
+            errors: AtomicUsize::new(0),
+            options,
+            root_device: if options.one_file_system {
+                device_of(&std::fs::metadata(&root)?)
+            } else {
+                None
+            },
+            root,
+            quiet,
+        };
+        let start = Instant::now();
+
+        if !quiet {
+            eprintln!("Scanning {}...", path.display());
+        }
+        let result = Self::scan_with_progress(path, 0, &ctx);
+        if !quiet {
+            eprintln!();
+        }
+
+        let stats = ScanStats {
+            items: ctx.items.load(Ordering::Relaxed),
+            errors: ctx.errors.load(Ordering::Relaxed),
+            duration: start.elapsed(),
+        };
+        result.map(|node| (node, stats))
+    }
+
+    /// 由路径列表构建节点树
+    ///
+    /// 只统计列表中的路径，不递归扫描目录：相对路径按当前目录解析，树根为所有路径的公共祖先，
+    /// 中间目录按需补齐，目录大小为其下列出文件之和。无法访问的路径会被跳过并计入错误数。
+    ///
+    /// # 错误
+    /// 如果列表中没有可访问的路径，则返回错误。
+    pub fn from_paths(
+        paths: Vec<PathBuf>,
+        size_format: SizeFormat,
+    ) -> anyhow::Result<(Node, ScanStats)> {
+        let start = Instant::now();
+        let cwd = std::env::current_dir()?;
+        let mut stats = ScanStats::default();
+
+        eprintln!("Reading {} listed paths...", paths.len());
+        let mut entries: Vec<Listed> = Vec::with_capacity(paths.len());
+        for path in paths {
+            let path = normalize(&cwd.join(path));
+            match std::fs::metadata(&path) {
+                Ok(meta) => {
+                    stats.items += 1;
+                    entries.push((
+                        path,
+                        (!meta.is_dir()).then(|| (meta.len(), disk_size_of(&meta))),
+                        meta.modified().ok(),
+                    ));
+                }
+                Err(e) => {
+                    stats.errors += 1;
+                    eprintln!("✗ Skipped {}: {}", path.display(), e);
+                }
+            }
+        }
+        entries.sort();
+        entries.dedup_by(|a, b| a.0 == b.0);
+        if entries.is_empty() {
+            bail!("No accessible paths in the list");
+        }
+
+        // 公共祖先；只列出单个文件时取其父目录
+        let mut root = entries[0].0.clone();
+        for (path, ..) in &entries[1..] {
+            while !path.starts_with(&root) {
+                root.pop();
+            }
+        }
+        if entries
+            .iter()
+            .any(|(p, size, _)| *p == root && size.is_some())
+        {
+            root.pop();
+        }
+        let root_modified = entries.iter().find(|(p, ..)| *p == root).and_then(|e| e.2);
+        entries.retain(|(p, ..)| *p != root);
+
+        let node = Self::build_listed(root, root_modified, &entries);
+        stats.duration = start.elapsed();
+        eprintln!(
+            "✓ {} ({} paths, {}) in {:.2}s",
+            node.path.display(),
+            stats.items,
+            size_format.format(node.size),
+            stats.duration.as_secs_f64(),
+        );
+        Ok((node, stats))
+    }
+
+    /// 由 `dir` 之下的已排序条目构建目录节点
+    fn build_listed(dir: PathBuf, modified: Option<SystemTime>, entries: &[Listed]) -> Node {
+        let mut children = Vec::new();
+        let mut rest = entries;
+        while let Some((first, ..)) = rest.first() {
+            // 按 `dir` 下的第一级名称分组；排序保证同组条目相邻
+            let child_path = match first
+                .strip_prefix(&dir)
+                .ok()
+                .and_then(|r| r.components().next())
+            {
+                Some(name) => dir.join(name),
+                None => first.clone(),
+            };
+            let len = rest
+                .iter()
+                .take_while(|(p, ..)| p.starts_with(&child_path))
+                .count();
+            let (group, tail) = rest.split_at(len);
+            rest = tail;
+
+            let node = match group {
+                [(p, Some((size, disk_size)), modified)] if *p == child_path => Node {
+                    path: child_path,
+                    size: *size,
+                    disk_size: *disk_size,
+                    kind: File,
+                    modified: *modified,
+                },
+                // 未列出的中间目录没有修改时间
+                [(p, _, modified), inner @ ..] if *p == child_path => {
+                    Self::build_listed(child_path, *modified, inner)
+                }
+                _ => Self::build_listed(child_path, None, group),
+            };
+            children.push(node);
+        }
+
+        sort_children(&mut children);
+        Node {
+            path: dir,
+            size: children.iter().map(|c| c.size).sum(),
+            disk_size: children.iter().map(|c| c.disk_size).sum(),
+            kind: Directory(DirProperty::new(children)),
+            modified,
+        }
     }
 
     /// 带进度显示的递归扫描
@@ -79,69 +481,88 @@ impl Node {
     /// # 参数
     /// * `path` - 要扫描的文件系统路径
     /// * `depth` - 当前递归深度（根目录为 0）
-    /// * `total_count` - 用于跟踪扫描总项数的共享原子计数器
+    /// * `ctx` - 扫描选项，以及用于跟踪扫描总项数与错误数的共享原子计数器
     ///
     /// # 进度显示
-    /// - 每扫描 100 项向标准错误输出显示一次进度
+    /// - 每扫描 100 项向标准错误输出显示一次进度（静默扫描时不显示）
     /// - 对于深度为 0 或 1 的目录，显示详细统计信息（目录/文件计数、大小、时间），以避免输出过多信息
     ///
     /// # 错误处理
-    /// - 跳过无法访问的条目，继续扫描
+    /// - 跳过无法访问的条目，继续扫描，并计入错误数
     /// - 仅对顶层条目（深度 ≤ 1）记录错误到标准错误输出
-    fn scan_with_progress(
-        path: PathBuf,
-        depth: usize,
-        total_count: &AtomicUsize,
-    ) -> anyhow::Result<Node> {
+    /// - 根路径本身无法访问时返回错误
+    ///
+    /// # 扫描选项
+    /// - 被排除的条目不计入树中
+    /// - 启用 `one_file_system` 时，其他文件系统的挂载点保留为空目录
+    fn scan_with_progress(path: PathBuf, depth: usize, ctx: &ScanContext) -> anyhow::Result<Node> {
         let start = Instant::now();
         let meta = std::fs::metadata(&path)?;
 
+        // 挂载点：不进入其他文件系统
+        if meta.is_dir() && ctx.root_device.is_some() && device_of(&meta) != ctx.root_device {
+            return Ok(Node {
+                path,
+                size: 0,
+                disk_size: 0,
+                kind: Directory(DirProperty::new(Vec::new())),
+                modified: meta.modified().ok(),
+            });
+        }
+
         if meta.is_dir() {
             let mut children: Vec<Node> = std::fs::read_dir(&path)?
                 .filter_map(|entry_result| {
                     entry_result
                         .map_err(|e| {
-                            if depth <= 1 {
+                            ctx.errors.fetch_add(1, Ordering::Relaxed);
+                            if ctx.reports(depth) {
                                 eprintln!("\n✗ Skipped reading a directory entry: {}", e);
                             }
                             e
                         })
                         .ok()
                 })
-                .map(|entry| {
+                .filter(|entry| !ctx.options.is_excluded(&ctx.root, &entry.path()))
+                .filter_map(|entry| {
                     let child_path = entry.path();
-                    let child_node = Self::scan_with_progress(child_path, depth + 1, total_count)?;
+                    let child_node = match Self::scan_with_progress(child_path, depth + 1, ctx) {
+                        Ok(node) => node,
+                        Err(e) => {
+                            ctx.errors.fetch_add(1, Ordering::Relaxed);
+                            if ctx.reports(depth) {
+                                eprintln!("\n✗ Skipped {}: {}", entry.path().display(), e);
+                            }
+                            return None;
+                        }
+                    };
 
-                    let count = total_count.fetch_add(1, Ordering::Relaxed) + 1;
-                    if count % 100 == 0 {
+                    let count = ctx.items.fetch_add(1, Ordering::Relaxed) + 1;
+                    if !ctx.quiet && count.is_multiple_of(100) {
                         eprint!("\rScanned {} items...", count);
                         std::io::Write::flush(&mut std::io::stderr()).ok();
                     }
 
-                    Ok(child_node)
+                    Some(child_node)
                 })
-                .collect::<anyhow::Result<Vec<Node>>>()?;
+                .collect();
 
             let dir_count = children.iter().filter(|c| c.kind.is_dir()).count();
             let file_count = children.len() - dir_count;
 
-            // 目录优先，按路径排序
-            children.sort_by(|a, b| match (&a.kind, &b.kind) {
-                (Directory(_), File) => std::cmp::Ordering::Less,
-                (File, Directory(_)) => std::cmp::Ordering::Greater,
-                _ => a.path.cmp(&b.path),
-            });
+            sort_children(&mut children);
 
             let total_size: u64 = children.iter().map(|c| c.size).sum();
+            let disk_size = disk_size_of(&meta) + children.iter().map(|c| c.disk_size).sum::<u64>();
 
             // 顶层目录打印统计
-            if depth <= 1 {
+            if ctx.reports(depth) {
                 eprintln!(
-                    "\n✓ {} ({} dirs, {} files, {:.1} MB) in {:.2}s",
+                    "\n✓ {} ({} dirs, {} files, {}) in {:.2}s",
                     path.display(),
                     dir_count,
                     file_count,
-                    total_size as f64 / 1024.0 / 1024.0,
+                    ctx.options.size_format.format(total_size),
                     start.elapsed().as_secs_f64(),
                 );
             }
@@ -149,14 +570,71 @@ impl Node {
             Ok(Node {
                 path,
                 size: total_size,
-                kind: Directory(DirProperty { children }),
+                disk_size,
+                kind: Directory(DirProperty::new(children)),
+                modified: meta.modified().ok(),
             })
         } else {
             Ok(Node {
                 path,
                 size: meta.len(),
+                disk_size: disk_size_of(&meta),
                 kind: File,
+                modified: meta.modified().ok(),
             })
         }
     }
 }
+
+/// 路径列表中的条目：(路径, (文件大小, 磁盘占用), 修改时间)，目录的大小为 None
+type Listed = (PathBuf, Option<(u64, u64)>, Option<SystemTime>);
+
+/// 目录优先，按路径排序
+fn sort_children(children: &mut [Node]) {
+    children.sort_by(|a, b| match (&a.kind, &b.kind) {
+        (Directory(_), File) => std::cmp::Ordering::Less,
+        (File, Directory(_)) => std::cmp::Ordering::Greater,
+        _ => a.path.cmp(&b.path),
+    });
+}
+
+/// 按词法规范化路径（去除 `.`，回退 `..`），不访问文件系统
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// 获取条目占用的磁盘空间（分配的 512 字节块）
+#[cfg(unix)]
+fn disk_size_of(meta: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.blocks() * 512
+}
+
+/// 获取条目占用的磁盘空间（非 Unix 平台以文件大小近似）
+#[cfg(not(unix))]
+fn disk_size_of(meta: &std::fs::Metadata) -> u64 {
+    if meta.is_dir() { 0 } else { meta.len() }
+}
+
+/// 获取文件所在设备号
+#[cfg(unix)]
+pub(crate) fn device_of(meta: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.dev())
+}
+
+/// 获取文件所在设备号（非 Unix 平台不支持）
+#[cfg(not(unix))]
+pub(crate) fn device_of(_meta: &std::fs::Metadata) -> Option<u64> {
+    None
+}
@@ -0,0 +1,256 @@
+//! 模式匹配
+//!
+//! 提供不依赖外部库的 glob 匹配，支持 `*`、`**`、`?` 与字符类 `[...]`；
+//! 以及搜索使用的 [`Matcher`]，可按子串、glob 或正则匹配名称。
+
+use anyhow::Context;
+use regex::Regex;
+
+/// 名称匹配器
+#[derive(Clone, Debug)]
+pub(crate) enum Matcher {
+    Substring { needle: String, ignore_case: bool }, // 子串
+    Glob(String),                                    // glob，匹配整个名称
+    Regex(Regex),                                    // 正则，`re:` 前缀
+}
+
+impl Matcher {
+    /// 解析模式
+    ///
+    /// - `re:` 前缀按正则解析
+    /// - 含 `*`、`?`、`[` 时按 glob 解析
+    /// - 否则为子串匹配，模式全为小写时忽略大小写
+    pub(crate) fn parse(pattern: &str) -> anyhow::Result<Self> {
+        if let Some(re) = pattern.strip_prefix("re:") {
+            let regex = Regex::new(re).with_context(|| format!("Invalid regex `{re}`"))?;
+            Ok(Matcher::Regex(regex))
+        } else if pattern.contains(['*', '?', '[']) {
+            Ok(Matcher::Glob(pattern.to_string()))
+        } else {
+            let ignore_case = !pattern.chars().any(char::is_uppercase);
+            Ok(Matcher::Substring {
+                needle: pattern.to_string(),
+                ignore_case,
+            })
+        }
+    }
+
+    /// 判断名称是否匹配
+    pub(crate) fn is_match(&self, name: &str) -> bool {
+        match self {
+            Matcher::Substring {
+                needle,
+                ignore_case: true,
+            } => name.to_lowercase().contains(needle.as_str()),
+            Matcher::Substring { needle, .. } => name.contains(needle.as_str()),
+            Matcher::Glob(pattern) => glob_match(pattern, name),
+            Matcher::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// 判断文本是否匹配 glob 模式
+///
+/// - `*` 匹配除 `/` 外的任意字符序列
+/// - `**` 匹配包括 `/` 在内的任意字符序列，`**/` 还可匹配零层目录
+/// - `?` 匹配除 `/` 外的单个字符
+/// - `[abc]`、`[a-z]`、`[!x]` 匹配字符类
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let tokens = compile(pattern);
+    // 模拟非确定自动机：`states[i]` 表示已匹配前 i 个记号，耗时 O(模式长度 × 文本长度)
+    let mut states = vec![false; tokens.len() + 1];
+    states[0] = true;
+    close(&tokens, &mut states);
+    for c in text.chars() {
+        let mut next = vec![false; tokens.len() + 1];
+        for (i, token) in tokens.iter().enumerate() {
+            if !states[i] {
+                continue;
+            }
+            match token {
+                Token::Char(x) if *x == c => next[i + 1] = true,
+                Token::Any if c != '/' => next[i + 1] = true,
+                Token::Class(class) if c != '/' && class.matches(c) => next[i + 1] = true,
+                Token::Star if c != '/' => next[i] = true,
+                Token::Globstar => next[i] = true,
+                _ => {}
+            }
+        }
+        close(&tokens, &mut next);
+        if !next.contains(&true) {
+            return false;
+        }
+        states = next;
+    }
+    states[tokens.len()]
+}
+
+/// 模式记号
+enum Token {
+    Char(char),   // 普通字符
+    Any,          // `?`
+    Class(Class), // `[...]`
+    Star,         // `*`
+    Globstar,     // `**`
+    ZeroDirs,     // `**/` 的起点，不消耗字符，可跳过其后的 `**` 与 `/` 以匹配零层目录
+}
+
+/// 字符类
+struct Class {
+    negated: bool,   // `[!...]` 或 `[^...]`
+    body: Vec<char>, // 类内的字符与范围
+}
+
+impl Class {
+    fn matches(&self, c: char) -> bool {
+        let body = &self.body;
+        let mut found = false;
+        let mut j = 0;
+        while j < body.len() {
+            if j + 2 < body.len() && body[j + 1] == '-' {
+                found |= body[j] <= c && c <= body[j + 2];
+                j += 3;
+            } else {
+                found |= body[j] == c;
+                j += 1;
+            }
+        }
+        found != self.negated
+    }
+}
+
+/// 将模式拆分为记号
+fn compile(pattern: &str) -> Vec<Token> {
+    let p: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < p.len() {
+        match p[i] {
+            '*' if p.get(i + 1) == Some(&'*') => {
+                let slash = p.get(i + 2) == Some(&'/');
+                if slash {
+                    tokens.extend([Token::ZeroDirs, Token::Globstar, Token::Char('/')]);
+                } else {
+                    tokens.push(Token::Globstar);
+                }
+                i += if slash { 3 } else { 2 };
+            }
+            '*' => {
+                tokens.push(Token::Star);
+                i += 1;
+            }
+            '?' => {
+                tokens.push(Token::Any);
+                i += 1;
+            }
+            '[' => match parse_class(&p[i..]) {
+                Some((class, len)) => {
+                    tokens.push(Token::Class(class));
+                    i += len;
+                }
+                // 未闭合的 `[` 按普通字符处理
+                None => {
+                    tokens.push(Token::Char('['));
+                    i += 1;
+                }
+            },
+            c => {
+                tokens.push(Token::Char(c));
+                i += 1;
+            }
+        }
+    }
+    tokens
+}
+
+/// 沿不消耗字符的转移扩展状态集合；转移只向后，一次正向遍历即可
+fn close(tokens: &[Token], states: &mut [bool]) {
+    for (i, token) in tokens.iter().enumerate() {
+        if !states[i] {
+            continue;
+        }
+        match token {
+            Token::Star | Token::Globstar => states[i + 1] = true,
+            Token::ZeroDirs => {
+                states[i + 1] = true;
+                states[i + 3] = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 解析字符类，返回字符类和模式中占用的长度
+fn parse_class(p: &[char]) -> Option<(Class, usize)> {
+    let mut i = 1;
+    let negated = matches!(p.get(i), Some('!' | '^'));
+    if negated {
+        i += 1;
+    }
+    let body_start = i;
+    // 紧跟在开头的 `]` 视为普通字符
+    if p.get(i) == Some(&']') {
+        i += 1;
+    }
+    while p.get(i)? != &']' {
+        i += 1;
+    }
+    let body = p[body_start..i].to_vec();
+    Some((Class { negated, body }, i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_stays_within_one_level() {
+        assert!(glob_match("*.rs", "main.rs"));
+        assert!(!glob_match("*.rs", "src/main.rs"));
+        assert!(glob_match("src/*", "src/main.rs"));
+        assert!(glob_match("a?c", "abc"));
+        assert!(!glob_match("a?c", "a/c"));
+    }
+
+    #[test]
+    fn globstar_spans_levels() {
+        assert!(glob_match("**", "a/b/c"));
+        assert!(glob_match("a**c", "a/b/c"));
+        assert!(glob_match("**/*.rs", "main.rs"));
+        assert!(glob_match("**/*.rs", "src/ui/main.rs"));
+        assert!(glob_match("a/**/b", "a/b"));
+        assert!(glob_match("a/**/b", "a/x/y/b"));
+        assert!(!glob_match("a/**/b", "a/xb"));
+        assert!(glob_match("**/", "/"));
+    }
+
+    #[test]
+    fn character_classes() {
+        assert!(glob_match("[a-c]x", "bx"));
+        assert!(!glob_match("[a-c]x", "dx"));
+        assert!(glob_match("[!a-c]x", "dx"));
+        assert!(!glob_match("[!a-c]x", "bx"));
+        assert!(glob_match("[^a-c]x", "dx"));
+        assert!(glob_match("[]]", "]"));
+        assert!(glob_match("[]a]", "a"));
+        assert!(!glob_match("[!]]", "]"));
+        assert!(glob_match("a[b", "a[b"));
+        assert!(!glob_match("[/]", "/"));
+    }
+
+    #[test]
+    fn globstar_does_not_backtrack_exponentially() {
+        let text = "a".repeat(200);
+        assert!(!glob_match("**a**a**a**a**a**a**a**a**a**a**b", &text));
+        assert!(!glob_match("*a*a*a*a*a*a*a*a*a*a*b", &text));
+    }
+
+    #[test]
+    fn matcher_kinds() {
+        assert!(Matcher::parse("log").unwrap().is_match("Build.LOG"));
+        assert!(!Matcher::parse("Log").unwrap().is_match("build.log"));
+        assert!(Matcher::parse("*.log").unwrap().is_match("build.log"));
+        assert!(Matcher::parse("re:^b.*g$").unwrap().is_match("build.log"));
+        assert!(Matcher::parse("re:(").is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! 文本报告
+//!
+//! 本模块将 [`Node`] 树渲染为非交互的纯文本树（类似 `tree` / `du -h`）或 Markdown 报告，子项顺序与交互界面一致（见 [`compare_nodes`]）。
+
+use crate::model::{Node, NodeKind::*};
+use crate::size::SizeFormat;
+use crate::ui_state::{SortMode, compare_nodes};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// 报告选项
+#[derive(Clone, Copy, Debug)]
+pub struct ReportOptions {
+    pub max_depth: Option<usize>, // 最大深度（根为 0）
+    pub top: Option<usize>,       // 每个目录最多显示的子项数
+    pub sort_mode: SortMode,      // 排序模式
+    pub size_format: SizeFormat,  // 大小格式
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            top: None,
+            sort_mode: SortMode::SizeDesc,
+            size_format: SizeFormat::default(),
+        }
+    }
+}
+
+/// Markdown 表格默认行数
+const DEFAULT_TABLE_ROWS: usize = 10;
+
+/// 输出纯文本树
+///
+/// 每行依次为大小、占根目录的百分比和带框线的名称。超出 `top` 的子项会合并为一行汇总。
+pub fn write_tree(node: &Node, options: &ReportOptions, out: &mut impl Write) -> io::Result<()> {
+    let total = node.size();
+    write_tree_line(
+        out,
+        options.size_format,
+        node.size(),
+        total,
+        "",
+        &node.root_label(),
+    )?;
+    let mut guides = String::new();
+    write_tree_children(node, options, total, 1, &mut guides, out)
+}
+
+/// 递归输出子项
+fn write_tree_children(
+    node: &Node,
+    options: &ReportOptions,
+    total: u64,
+    depth: usize,
+    guides: &mut String,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let Directory(prop) = node.kind() else {
+        return Ok(());
+    };
+    if options.max_depth.is_some_and(|max| depth > max) {
+        return Ok(());
+    }
+
+    let mut children: Vec<&Node> = prop.children().iter().collect();
+    children.sort_by(|a, b| compare_nodes(a, b, options.sort_mode));
+    let shown = options
+        .top
+        .map_or(children.len(), |top| top.min(children.len()));
+    let (visible, hidden) = children.split_at(shown);
+
+    for (i, child) in visible.iter().enumerate() {
+        let is_last = i + 1 == visible.len() && hidden.is_empty();
+        let branch = if is_last { "└── " } else { "├── " };
+        let prefix = format!("{guides}{branch}");
+        // 扫描根显示完整路径
+        let name = if node.is_synthetic() {
+            child.root_label()
+        } else {
+            display_name(child)
+        };
+        write_tree_line(
+            out,
+            options.size_format,
+            child.size(),
+            total,
+            &prefix,
+            &name,
+        )?;
+
+        let len = guides.len();
+        guides.push_str(if is_last { "    " } else { "│   " });
+        write_tree_children(child, options, total, depth + 1, guides, out)?;
+        guides.truncate(len);
+    }
+
+    if !hidden.is_empty() {
+        let hidden_size: u64 = hidden.iter().map(|c| c.size()).sum();
+        let label = format!("… {} more entries", hidden.len());
+        write_tree_line(
+            out,
+            options.size_format,
+            hidden_size,
+            total,
+            &format!("{guides}└── "),
+            &label,
+        )?;
+    }
+    Ok(())
+}
+
+/// 输出单行
+fn write_tree_line(
+    out: &mut impl Write,
+    format: SizeFormat,
+    size: u64,
+    total: u64,
+    prefix: &str,
+    name: &str,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:>10} {:>6.1}%  {}{}",
+        format.format(size),
+        percent(size, total),
+        prefix,
+        name
+    )
+}
+
+/// 输出 Markdown 报告
+///
+/// 包含总览、最大目录表和最大文件表；表格行数由 `top` 决定（默认 10）。
+pub fn write_markdown(
+    node: &Node,
+    options: &ReportOptions,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let total = node.size();
+    let (dir_count, file_count) = node.counts();
+    let rows = options.top.unwrap_or(DEFAULT_TABLE_ROWS);
+
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    collect_descendants(node, options.max_depth, 1, &mut dirs, &mut files);
+    dirs.sort_by(|a, b| compare_nodes(a, b, SortMode::SizeDesc));
+    files.sort_by(|a, b| compare_nodes(a, b, SortMode::SizeDesc));
+
+    writeln!(out, "# fswhy report: `{}`", node.root_label())?;
+    writeln!(out)?;
+    writeln!(out, "- Total size: {}", options.size_format.format(total))?;
+    writeln!(out, "- Directories: {dir_count}")?;
+    writeln!(out, "- Files: {file_count}")?;
+    writeln!(out)?;
+
+    writeln!(out, "## Largest directories")?;
+    writeln!(out)?;
+    writeln!(out, "| # | Path | Size | % of total | Files |")?;
+    writeln!(out, "|--:|------|-----:|-----------:|------:|")?;
+    for (i, dir) in dirs.iter().take(rows).enumerate() {
+        writeln!(
+            out,
+            "| {} | {} | {} | {:.1}% | {} |",
+            i + 1,
+            markdown_path(node.path(), dir.path()),
+            options.size_format.format(dir.size()),
+            percent(dir.size(), total),
+            dir.counts().1
+        )?;
+    }
+    writeln!(out)?;
+
+    writeln!(out, "## Largest files")?;
+    writeln!(out)?;
+    writeln!(out, "| # | Path | Size | % of total |")?;
+    writeln!(out, "|--:|------|-----:|-----------:|")?;
+    for (i, file) in files.iter().take(rows).enumerate() {
+        writeln!(
+            out,
+            "| {} | {} | {} | {:.1}% |",
+            i + 1,
+            markdown_path(node.path(), file.path()),
+            options.size_format.format(file.size()),
+            percent(file.size(), total)
+        )?;
+    }
+    Ok(())
+}
+
+/// 收集所有后代目录与文件
+fn collect_descendants<'a>(
+    node: &'a Node,
+    max_depth: Option<usize>,
+    depth: usize,
+    dirs: &mut Vec<&'a Node>,
+    files: &mut Vec<&'a Node>,
+) {
+    let Directory(prop) = node.kind() else {
+        return;
+    };
+    if max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
+    for child in prop.children() {
+        match child.kind() {
+            Directory(_) => {
+                dirs.push(child);
+                collect_descendants(child, max_depth, depth + 1, dirs, files);
+            }
+            File => files.push(child),
+        }
+    }
+}
+
+/// 节点显示名（目录带 `/` 后缀）
+fn display_name(node: &Node) -> String {
+    let name = node
+        .path()
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    if node.kind().is_dir() {
+        format!("{name}/")
+    } else {
+        name.into_owned()
+    }
+}
+
+/// 相对根目录的路径，转义表格分隔符
+fn markdown_path(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    format!("`{}`", relative.display()).replace('|', "\\|")
+}
+
+/// 百分比
+fn percent(size: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        size as f64 * 100.0 / total as f64
+    }
+}
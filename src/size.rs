@@ -0,0 +1,83 @@
+//! 大小格式
+//!
+//! 本模块将字节数格式化为带单位的文本，支持 1024 进制（KiB..EiB）、1000 进制（kB..EB）
+//! 与精确字节数三种单位制，小数位数可配置。交互界面、扫描进度与各类报告共用同一格式。
+
+use anyhow::bail;
+
+/// 单位制
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SizeUnits {
+    #[default]
+    Iec, // 1024 进制：KiB、MiB、GiB……
+    Si,    // 1000 进制：kB、MB、GB……
+    Bytes, // 精确字节数
+}
+
+impl SizeUnits {
+    /// 解析单位制名称
+    pub fn parse(text: &str) -> anyhow::Result<SizeUnits> {
+        match text.to_ascii_lowercase().as_str() {
+            "iec" | "binary" => Ok(SizeUnits::Iec),
+            "si" | "decimal" => Ok(SizeUnits::Si),
+            "bytes" | "b" => Ok(SizeUnits::Bytes),
+            _ => bail!("invalid units '{text}' (expected iec, si or bytes)"),
+        }
+    }
+
+    /// 下一个单位制，用于运行时切换
+    pub fn next(self) -> SizeUnits {
+        match self {
+            SizeUnits::Iec => SizeUnits::Si,
+            SizeUnits::Si => SizeUnits::Bytes,
+            SizeUnits::Bytes => SizeUnits::Iec,
+        }
+    }
+
+    /// 名称，用于提示
+    pub fn label(self) -> &'static str {
+        match self {
+            SizeUnits::Iec => "IEC (KiB, MiB, GiB…)",
+            SizeUnits::Si => "SI (kB, MB, GB…)",
+            SizeUnits::Bytes => "exact bytes",
+        }
+    }
+}
+
+/// 大小格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeFormat {
+    pub units: SizeUnits, // 单位制
+    pub precision: usize, // 小数位数
+}
+
+impl Default for SizeFormat {
+    fn default() -> Self {
+        Self {
+            units: SizeUnits::Iec,
+            precision: 1,
+        }
+    }
+}
+
+impl SizeFormat {
+    /// 格式化字节数；不足一个单位时按整数字节显示
+    pub fn format(self, size: u64) -> String {
+        let (base, units): (f64, [&str; 6]) = match self.units {
+            SizeUnits::Iec => (1024.0, ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]),
+            SizeUnits::Si => (1000.0, ["kB", "MB", "GB", "TB", "PB", "EB"]),
+            SizeUnits::Bytes => return format!("{size} B"),
+        };
+        let mut value = size as f64;
+        if value < base {
+            return format!("{size} B");
+        }
+        let mut unit = 0;
+        value /= base;
+        while value >= base && unit + 1 < units.len() {
+            value /= base;
+            unit += 1;
+        }
+        format!("{:.*} {}", self.precision, value, units[unit])
+    }
+}
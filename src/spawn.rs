@@ -0,0 +1,168 @@
+//! 外部程序
+//!
+//! 本模块根据选中的条目构造要运行的外部程序（shell、编辑器、分页器或 `xdg-open`）。
+//! 程序由主循环在离开全屏界面后前台运行，退出后恢复界面。
+//!
+//! 自定义命令则直接通过 `sh -c` 运行并捕获输出，不离开全屏界面。
+
+use crate::model::{Node, NodeKind::*};
+use anyhow::bail;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output, Stdio};
+
+/// 程序类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Program {
+    Shell,  // `$SHELL`，在选中的目录（文件则为其父目录）中启动
+    Editor, // `$VISUAL` / `$EDITOR`
+    Pager,  // `$PAGER`
+    Open,   // `xdg-open`
+}
+
+/// 待运行的外部程序
+pub struct Spawn {
+    pub program: String,         // 命令，可带参数，由 `sh -c` 解释
+    pub args: Vec<OsString>,     // 追加在命令后的参数
+    pub dir: Option<PathBuf>,    // 工作目录
+    pub rescan: Option<PathBuf>, // 退出后重新扫描的子树
+}
+
+impl Spawn {
+    /// 为条目构造外部程序
+    pub fn new(program: Program, node: &Node) -> anyhow::Result<Spawn> {
+        if node.is_synthetic() {
+            bail!("Select one of the scan roots first");
+        }
+        let path = node.path().to_path_buf();
+        let is_dir = matches!(node.kind(), Directory(_));
+        if is_dir && matches!(program, Program::Editor | Program::Pager) {
+            bail!("Cannot open a directory in the {}", describe(program));
+        }
+
+        let (command, default) = match program {
+            Program::Shell => (env_command(&["SHELL"]), "/bin/sh"),
+            Program::Editor => (env_command(&["VISUAL", "EDITOR"]), "vi"),
+            Program::Pager => (env_command(&["PAGER"]), "less"),
+            Program::Open => (None, "xdg-open"),
+        };
+        let program_name = command.unwrap_or_else(|| default.to_string());
+
+        let spawn = match program {
+            Program::Shell => {
+                let dir = if is_dir { path } else { parent_dir(&path) };
+                Spawn {
+                    program: program_name,
+                    args: Vec::new(),
+                    dir: Some(dir.clone()),
+                    rescan: Some(dir),
+                }
+            }
+            Program::Editor => Spawn {
+                program: program_name,
+                args: vec![path.clone().into_os_string()],
+                dir: None,
+                rescan: Some(path),
+            },
+            Program::Pager | Program::Open => Spawn {
+                program: program_name,
+                args: vec![path.into_os_string()],
+                dir: None,
+                rescan: None,
+            },
+        };
+        Ok(spawn)
+    }
+
+    /// 前台运行并等待退出
+    ///
+    /// 与 git 运行 `$EDITOR` 的方式相同，命令经 `sh -c '<命令> "$@"'` 解释，
+    /// 因此环境变量中可以使用引号和带空格的路径，参数则原样传递。
+    pub fn run(&self) -> io::Result<ExitStatus> {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(format!("{} \"$@\"", self.program))
+            .arg("sh")
+            .args(&self.args);
+        if let Some(dir) = &self.dir {
+            command.current_dir(dir);
+        }
+        command.status()
+    }
+}
+
+/// 以条目信息替换命令模板中的占位符，替换值经过 shell 引用
+///
+/// 支持 `{path}`、`{name}`、`{parent}`、`{size}`（字节数），其他花括号原样保留。
+pub(crate) fn expand_command(template: &str, node: &Node) -> anyhow::Result<String> {
+    if node.is_synthetic() {
+        bail!("Select one of the scan roots first");
+    }
+    let path = node.path();
+    let mut script = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        script.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let value = match &rest[1..end] {
+            "path" => shell_quote(&path.to_string_lossy()),
+            "name" => shell_quote(&path.file_name().unwrap_or_default().to_string_lossy()),
+            "parent" => shell_quote(&parent_dir(path).to_string_lossy()),
+            "size" => node.size().to_string(),
+            _ => {
+                script.push('{');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        script.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    script.push_str(rest);
+    Ok(script)
+}
+
+/// 通过 `sh -c` 运行命令并捕获输出，标准输入为空
+pub(crate) fn run_captured(script: &str) -> io::Result<Output> {
+    Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .stdin(Stdio::null())
+        .output()
+}
+
+/// 用单引号引用 shell 参数
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// 程序类型的描述，用于提示
+fn describe(program: Program) -> &'static str {
+    match program {
+        Program::Shell => "shell",
+        Program::Editor => "editor",
+        Program::Pager => "pager",
+        Program::Open => "default application",
+    }
+}
+
+/// 读取第一个非空的环境变量
+fn env_command(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+}
+
+/// 父目录（相对路径没有父目录时为当前目录）
+pub(crate) fn parent_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
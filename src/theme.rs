@@ -4,6 +4,7 @@ use std::env;
 use std::fs;
 use std::path::Path;
 
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
 /// 主题配置
@@ -19,6 +20,8 @@ pub(crate) struct Theme {
     pub(crate) file: Color,
     #[serde(default)]
     pub(crate) error: Color,
+    #[serde(default = "default_search_match")]
+    pub(crate) search_match: Color,
     #[serde(default)]
     pub(crate) highlight_start: Color,
     #[serde(default)]
@@ -136,7 +139,7 @@ define_preset_colors! {
 #[serde(untagged)]
 pub(crate) enum Color {
     Preset { name: String },
-    RGB { r: u8, g: u8, b: u8 },
+    Rgb { r: u8, g: u8, b: u8 },
 }
 
 impl Default for Theme {
@@ -157,28 +160,29 @@ impl Default for Theme {
             error: Color::Preset {
                 name: "red".to_string(),
             },
+            search_match: default_search_match(),
             highlight_start: Color::Preset {
                 name: "invert".to_string(),
             },
             highlight_end: Color::Preset {
                 name: "reset".to_string(),
             },
-            dir_gradient_start: Color::RGB {
+            dir_gradient_start: Color::Rgb {
                 r: 58,
                 g: 123,
                 b: 213,
             },
-            dir_gradient_end: Color::RGB {
+            dir_gradient_end: Color::Rgb {
                 r: 0,
                 g: 210,
                 b: 255,
             },
-            file_gradient_start: Color::RGB {
+            file_gradient_start: Color::Rgb {
                 r: 180,
                 g: 180,
                 b: 180,
             },
-            file_gradient_end: Color::RGB {
+            file_gradient_end: Color::Rgb {
                 r: 255,
                 g: 200,
                 b: 120,
@@ -187,6 +191,13 @@ impl Default for Theme {
     }
 }
 
+/// 搜索匹配项的默认颜色
+fn default_search_match() -> Color {
+    Color::Preset {
+        name: "yellow".to_string(),
+    }
+}
+
 impl Default for Color {
     fn default() -> Self {
         Color::Preset {
@@ -203,7 +214,7 @@ impl Color {
                 let preset = PresetColor::parse(name)?;
                 Ok(preset.to_ansi().to_string())
             }
-            Color::RGB { r, g, b } => Ok(format!("\x1b[38;2;{};{};{}m", r, g, b)),
+            Color::Rgb { r, g, b } => Ok(format!("\x1b[38;2;{};{};{}m", r, g, b)),
         }
     }
 
@@ -214,7 +225,7 @@ impl Color {
                 let preset = PresetColor::parse(name)?;
                 Ok(preset.to_rgb())
             }
-            Color::RGB { r, g, b } => Ok((*r, *g, *b)),
+            Color::Rgb { r, g, b } => Ok((*r, *g, *b)),
         }
     }
 
@@ -243,6 +254,7 @@ impl Theme {
         self.dir.validate()?;
         self.file.validate()?;
         self.error.validate()?;
+        self.search_match.validate()?;
         self.highlight_start.validate()?;
         self.highlight_end.validate()?;
         self.dir_gradient_start.validate()?;
@@ -253,13 +265,22 @@ impl Theme {
     }
 }
 
+/// 加载主题：指定路径时必须加载成功，否则回退到环境变量或默认路径
+pub(crate) fn load_theme(path: Option<&Path>) -> anyhow::Result<Theme> {
+    match path {
+        Some(path) => Theme::load_from_file(path)
+            .with_context(|| format!("Cannot load theme {}", path.display())),
+        None => Ok(load_theme_from_env_or_default()),
+    }
+}
+
 /// 从环境变量或默认路径加载主题
 pub(crate) fn load_theme_from_env_or_default() -> Theme {
     // 优先从环境变量加载
-    if let Ok(path) = env::var("FSWHY_THEME") {
-        if let Ok(theme) = Theme::load_from_file(Path::new(&path)) {
-            return theme;
-        }
+    if let Ok(path) = env::var("FSWHY_THEME")
+        && let Ok(theme) = Theme::load_from_file(Path::new(&path))
+    {
+        return theme;
     }
     // 其次从当前目录加载
     if let Ok(theme) = Theme::load_from_file(Path::new("theme.toml")) {
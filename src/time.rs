@@ -0,0 +1,38 @@
+//! 时间格式
+//!
+//! 本模块将时间戳格式化为本地时间，供交互界面与回收站信息文件共用。
+
+use std::time::SystemTime;
+
+/// 本地时间，格式为 `YYYY-MM-DD hh:mm:ss`
+#[cfg(unix)]
+pub(crate) fn format_local_time(time: SystemTime) -> String {
+    let secs = match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as libc::time_t,
+        Err(e) => -(e.duration().as_secs() as libc::time_t),
+    };
+    // SAFETY: localtime_r 写入调用方提供的 tm，不使用共享的静态缓冲区
+    let tm = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&secs, &mut tm);
+        tm
+    };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+/// 距 Unix 纪元的秒数（非 Unix 平台没有时区信息）
+#[cfg(not(unix))]
+pub(crate) fn format_local_time(time: SystemTime) -> String {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(d) => format!("@{}", d.as_secs()),
+        Err(_) => "-".to_string(),
+    }
+}
@@ -0,0 +1,186 @@
+//! 移到回收站
+//!
+//! 本模块按 freedesktop.org 回收站规范移动条目：与主目录回收站位于同一文件系统的条目移到
+//! `$XDG_DATA_HOME/Trash`，其他文件系统的条目移到该卷顶层的 `.Trash/$uid` 或 `.Trash-$uid`。
+//! 每个条目都写入 `info/*.trashinfo`，桌面文件管理器可据此还原。
+
+#[cfg(unix)]
+use crate::time::format_local_time;
+use anyhow::bail;
+use std::path::{Path, PathBuf};
+
+/// 将条目移到回收站，返回所用的回收站目录
+#[cfg(unix)]
+pub(crate) fn move_to_trash(path: &Path) -> anyhow::Result<PathBuf> {
+    use anyhow::Context;
+    use std::os::unix::fs::MetadataExt;
+
+    // 只规范化父目录，条目本身是符号链接时移动链接
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        bail!("Cannot move {} to trash", path.display());
+    };
+    let parent = if parent.as_os_str().is_empty() {
+        std::env::current_dir()?
+    } else {
+        parent.canonicalize()?
+    };
+    let path = parent.join(name);
+    let device = std::fs::symlink_metadata(&path)?.dev();
+
+    let home_trash = home_trash()?;
+    let (trash, info_path) = if device_of_nearest(&home_trash) == Some(device) {
+        (home_trash, path.clone())
+    } else {
+        let top = mount_point(&parent, device);
+        let trash = volume_trash(&top)
+            .with_context(|| format!("No usable trash directory on {}", top.display()))?;
+        // 卷回收站中记录相对卷顶层的路径
+        let relative = path.strip_prefix(&top).unwrap_or(&path).to_path_buf();
+        (trash, relative)
+    };
+
+    let files = trash.join("files");
+    let info = trash.join("info");
+    for dir in [&files, &info] {
+        create_private_dir(dir).with_context(|| format!("Cannot create {}", dir.display()))?;
+    }
+
+    // 先独占创建 info 文件以占用名称，重名时追加序号
+    let contents = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        percent_encode(&info_path),
+        local_timestamp()
+    );
+    let mut counter = 1;
+    let (info_file, target) = loop {
+        let mut trashed_name = name.to_os_string();
+        if counter > 1 {
+            trashed_name.push(format!(".{counter}"));
+        }
+        let mut info_name = trashed_name.clone();
+        info_name.push(".trashinfo");
+        let info_file = info.join(info_name);
+        let target = files.join(&trashed_name);
+        counter += 1;
+        if std::fs::symlink_metadata(&target).is_ok() {
+            continue;
+        }
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_file)
+        {
+            Ok(mut file) => {
+                std::io::Write::write_all(&mut file, contents.as_bytes())?;
+                break (info_file, target);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Cannot write {}", info_file.display()));
+            }
+        }
+    };
+
+    if let Err(e) = std::fs::rename(&path, &target) {
+        std::fs::remove_file(&info_file).ok();
+        return Err(e).with_context(|| format!("Cannot move {} to trash", path.display()));
+    }
+    Ok(trash)
+}
+
+/// 将条目移到回收站（非 Unix 平台不支持）
+#[cfg(not(unix))]
+pub(crate) fn move_to_trash(_path: &Path) -> anyhow::Result<PathBuf> {
+    bail!("Trash is not supported on this platform")
+}
+
+/// 主目录回收站：`$XDG_DATA_HOME/Trash`，默认 `~/.local/share/Trash`
+#[cfg(unix)]
+fn home_trash() -> anyhow::Result<PathBuf> {
+    let data_home = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+        _ => match std::env::var_os("HOME") {
+            Some(home) => Path::new(&home).join(".local/share"),
+            None => bail!("Cannot locate the home trash: HOME is not set"),
+        },
+    };
+    Ok(data_home.join("Trash"))
+}
+
+/// 卷回收站：优先 `$topdir/.Trash/$uid`（要求 `.Trash` 为设置了粘滞位的真实目录），否则 `$topdir/.Trash-$uid`
+#[cfg(unix)]
+fn volume_trash(top: &Path) -> anyhow::Result<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // SAFETY: getuid 总是成功且没有副作用
+    let uid = unsafe { libc::getuid() };
+    let shared = top.join(".Trash");
+    if let Ok(meta) = std::fs::symlink_metadata(&shared)
+        && meta.is_dir()
+        && meta.permissions().mode() & 0o1000 != 0
+    {
+        let trash = shared.join(uid.to_string());
+        if create_private_dir(&trash).is_ok() {
+            return Ok(trash);
+        }
+    }
+    let trash = top.join(format!(".Trash-{uid}"));
+    create_private_dir(&trash)?;
+    Ok(trash)
+}
+
+/// 创建仅属主可访问的目录（已存在时不修改）
+#[cfg(unix)]
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::DirBuilderExt;
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)
+}
+
+/// 路径所在文件系统的挂载点：向上查找仍在同一设备上的最高目录
+#[cfg(unix)]
+fn mount_point(dir: &Path, device: u64) -> PathBuf {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut top = dir;
+    while let Some(parent) = top.parent() {
+        match std::fs::metadata(parent) {
+            Ok(meta) if meta.dev() == device => top = parent,
+            _ => break,
+        }
+    }
+    top.to_path_buf()
+}
+
+/// 路径（不存在时取最近的已存在祖先）所在的设备号
+#[cfg(unix)]
+fn device_of_nearest(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    path.ancestors()
+        .find_map(|p| std::fs::metadata(p).ok())
+        .map(|meta| meta.dev())
+}
+
+/// 按 URL 规则百分号编码路径（保留 `/` 与非保留字符）
+#[cfg(unix)]
+fn percent_encode(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// 本地时间，格式为 `YYYY-MM-DDThh:mm:ss`
+#[cfg(unix)]
+fn local_timestamp() -> String {
+    format_local_time(std::time::SystemTime::now()).replacen(' ', "T", 1)
+}
@@ -2,13 +2,20 @@
 //!
 //! 本模块将内部的 [`UiState`] 转换为人类可读的终端界面，并将原始用户按键转换为可操作的 [`Action`]。
 
-use crate::model::NodeKind::*;
+use crate::columns::{Align, Column, ColumnKind, fit};
+use crate::config::CustomCommand;
+use crate::details::{describe, mode_string, user};
+use crate::model::{Node, NodeKind::*};
+use crate::size::SizeFormat;
+use crate::spawn::Program;
 use crate::theme::Color;
-use crate::ui_state::{Action, SortMode, UiState, ViewItem};
+use crate::time::format_local_time;
+use crate::ui_state::{Action, PromptKind, ShareBase, SortMode, UiState, ViewItem};
 
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
-use crossterm::{cursor, queue, terminal};
+use crossterm::{cursor, execute, queue, style, terminal};
 use std::io::{self, Write};
+use unicode_width::UnicodeWidthStr;
 
 /// raw mode守卫，析构时恢复
 pub struct RawModeGuard;
@@ -26,16 +33,95 @@ impl Drop for RawModeGuard {
     }
 }
 
-/// 渲染文件树
-pub fn render(state: &UiState) {
-    let stdout = io::stdout();
-    let mut out = stdout.lock();
-    let _ = queue!(
-        out,
-        terminal::Clear(terminal::ClearType::All),
-        cursor::MoveTo(0, 0)
-    );
+/// 备用屏幕守卫，析构时恢复原屏幕
+///
+/// 界面绘制在备用屏幕上并关闭自动换行，退出后终端回到启动前的内容，不污染滚动缓冲。
+pub struct AlternateScreenGuard;
 
+impl AlternateScreenGuard {
+    pub fn new() -> anyhow::Result<Self> {
+        execute!(
+            io::stdout(),
+            terminal::EnterAlternateScreen,
+            terminal::DisableLineWrap
+        )?;
+        Ok(Self)
+    }
+}
+
+impl Drop for AlternateScreenGuard {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            terminal::EnableLineWrap,
+            terminal::LeaveAlternateScreen
+        );
+    }
+}
+
+/// 差分渲染器
+///
+/// 保存上一帧的各行内容，只重绘发生变化的行，避免整屏清除造成的闪烁。
+pub struct Renderer {
+    previous: Vec<String>, // 上一帧
+    full_redraw: bool,     // 下一帧是否整屏重绘
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+            full_redraw: true,
+        }
+    }
+
+    /// 下一帧整屏重绘（终端尺寸变化后屏幕内容不可信）
+    pub fn invalidate(&mut self) {
+        self.previous.clear();
+        self.full_redraw = true;
+    }
+
+    /// 渲染文件树
+    pub fn render(&mut self, state: &UiState) {
+        let frame = build_frame(state);
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+
+        if self.full_redraw {
+            let _ = queue!(out, terminal::Clear(terminal::ClearType::All));
+            self.full_redraw = false;
+        }
+        for (row, line) in frame.iter().enumerate() {
+            if self.previous.get(row) == Some(line) {
+                continue;
+            }
+            let _ = queue!(
+                out,
+                cursor::MoveTo(0, row as u16),
+                style::Print(line),
+                terminal::Clear(terminal::ClearType::UntilNewLine)
+            );
+        }
+        for row in frame.len()..self.previous.len() {
+            let _ = queue!(
+                out,
+                cursor::MoveTo(0, row as u16),
+                terminal::Clear(terminal::ClearType::CurrentLine)
+            );
+        }
+
+        // 光标停在帮助栏的输入提示之后
+        let last_row = frame.len().saturating_sub(1);
+        let last_col = frame.last().map_or(0, |line| line.width());
+        let _ = queue!(out, cursor::MoveTo(last_col as u16, last_row as u16));
+        let _ = out.flush();
+        self.previous = frame;
+    }
+}
+
+/// 生成一帧的各行内容
+fn build_frame(state: &UiState) -> Vec<String> {
+    let mut lines = Vec::new();
     let view = state.flatten_view();
     let total = view.len();
     let max_idx_width = total.saturating_sub(1).to_string().len().max(1);
@@ -43,9 +129,7 @@ pub fn render(state: &UiState) {
 
     // 计算视口范围
     let cursor = state.cursor.min(total.saturating_sub(1));
-    let start = if total <= height {
-        0
-    } else if cursor + 1 <= height {
+    let start = if total <= height || cursor < height {
         0
     } else {
         (cursor + 1 - height).min(total.saturating_sub(height))
@@ -54,44 +138,81 @@ pub fn render(state: &UiState) {
     let remaining_above = start;
     let remaining_below = total.saturating_sub(end);
 
+    // 可见列及宽度：行号与大小默认按本屏内容自适应，未指定宽度的名称列占满剩余宽度
+    let columns: Vec<&Column> = state.config.columns.iter().filter(|c| !c.hidden).collect();
+    let size_width = |size_of: fn(&ViewItem<'_>) -> u64| {
+        view[start..end]
+            .iter()
+            .map(|item| state.size_format.format(size_of(item)).chars().count())
+            .max()
+            .unwrap_or(1)
+    };
+    let widths: Vec<Option<usize>> = columns
+        .iter()
+        .map(|c| match c.kind {
+            ColumnKind::Index => Some(c.width().unwrap_or(max_idx_width)),
+            ColumnKind::Size => Some(c.width().unwrap_or_else(|| size_width(|item| item.size))),
+            ColumnKind::Disk => Some(
+                c.width()
+                    .unwrap_or_else(|| size_width(|item| item.node.disk_size())),
+            ),
+            _ => c.width(),
+        })
+        .collect();
+    // 选中标记与标记列共 2 个字符，列之间以空格分隔
+    let used = 2 + widths.iter().flatten().sum::<usize>() + columns.len().saturating_sub(1);
+    let name_width = (state.terminal_size.0 as usize).saturating_sub(used).max(1);
+
+    // 占比基准（只在显示占比列或占比条时计算）
+    let share_bases = if columns
+        .iter()
+        .any(|c| matches!(c.kind, ColumnKind::Percent | ColumnKind::Bar))
+    {
+        share_bases(&view, state.share_base)
+    } else {
+        Vec::new()
+    };
+
     // 计算大小范围（用于渐变色）
     let (dir_min, dir_max) = size_range(&view, true).unwrap_or((0, 0));
     let (file_min, file_max) = size_range(&view, false).unwrap_or((0, 0));
 
     // 标题
-    let _ = write!(
-        out,
-        "--- File Tree (Total: {}, Showing: {}-{}) ---\r\n",
+    lines.push(format!(
+        "--- File Tree (Total: {}, Showing: {}-{}) ---",
         total,
         start,
         end.saturating_sub(1)
-    );
+    ));
+    let view_root = state.view_root();
+    let header = if std::ptr::eq(view_root, &state.root) {
+        roots_line(&state.root, state.size_format)
+    } else {
+        breadcrumb(&state.root, view_root)
+    };
+    lines.push(match &state.filter {
+        Some(active) => format!(
+            "{}  [Filter: {} | sizes: {}]",
+            header,
+            active.filter.text,
+            if state.filtered_sizes {
+                "matching"
+            } else {
+                "total"
+            }
+        ),
+        None => header,
+    });
     if remaining_above > 0 || remaining_below > 0 {
-        let _ = write!(
-            out,
-            "(More: above {}, below {})\r\n",
+        lines.push(format!(
+            "(More: above {}, below {})",
             remaining_above, remaining_below
-        );
+        ));
     }
 
     // 渲染每一行
     for (index, item) in view.iter().enumerate().skip(start).take(end - start) {
-        let prefix = "  ".repeat(item.depth);
-        let idx_str = format!("{:width$}", index, width = max_idx_width);
-        let icon = match item.node.kind() {
-            Directory(_) => {
-                if state.expanded_nodes.contains(&item.node) {
-                    "[-]"
-                } else {
-                    "[+]"
-                }
-            }
-            File => "   ",
-        };
-
-        let size = item.node.size();
-        let size_str = format_size(size);
-
+        let size = item.size;
         let is_selected = index == cursor;
         let (hl_start, hl_end) = if is_selected {
             (
@@ -102,9 +223,19 @@ pub fn render(state: &UiState) {
             (String::new(), String::new())
         };
         let selection = if is_selected { ">" } else { " " };
+        let mark = if state.marked.contains(item.node.path()) {
+            "*"
+        } else {
+            " "
+        };
 
-        // 渐变色
+        // 搜索匹配项高亮，其余按大小渐变
+        let is_match = state
+            .search
+            .as_ref()
+            .is_some_and(|search| search.matched.contains(item.node.path()));
         let name_color = match item.node.kind() {
+            _ if is_match => state.theme.search_match.to_ansi().unwrap_or_default(),
             Directory(_) => gradient_color(
                 size,
                 dir_min,
@@ -124,24 +255,98 @@ pub fn render(state: &UiState) {
         };
         let fg_reset = state.theme.fg_reset.to_ansi().unwrap_or_default();
 
-        let _ = write!(
-            out,
-            "{}{} {}{} {} {}{}{} ({}){}\r\n",
+        let fraction = match share_bases.get(index) {
+            Some(&base) if base > 0 => size as f64 / base as f64,
+            _ => 0.0,
+        };
+        // 所有者与权限列需要读取元数据
+        let meta = columns
+            .iter()
+            .any(|c| matches!(c.kind, ColumnKind::Owner | ColumnKind::Permissions))
+            .then(|| std::fs::metadata(item.node.path()).ok())
+            .flatten();
+
+        let cells: Vec<String> = columns
+            .iter()
+            .zip(&widths)
+            .map(|(column, width)| {
+                let width = width.unwrap_or(name_width);
+                let align = column.align();
+                let text = match column.kind {
+                    ColumnKind::Name => {
+                        let icon = match item.node.kind() {
+                            Directory(_) if state.expanded_nodes.contains(item.node.path()) => {
+                                "[-]"
+                            }
+                            Directory(_) => "[+]",
+                            File => "   ",
+                        };
+                        let prefix = format!("{}{} ", "  ".repeat(item.depth), icon);
+                        let name_room = width.saturating_sub(prefix.width());
+                        let name = fit(&display_name(&state.root, item), name_room, Align::Left);
+                        let name = name.trim_end();
+                        let padding = width.saturating_sub(prefix.width() + name.width());
+                        return format!(
+                            "{}{}{}{}{}",
+                            prefix,
+                            name_color,
+                            name,
+                            fg_reset,
+                            " ".repeat(padding)
+                        );
+                    }
+                    ColumnKind::Bar => {
+                        let bar = bar(fraction, width.saturating_sub(2).max(1));
+                        return format!("{}{}{}", name_color, fit(&bar, width, align), fg_reset);
+                    }
+                    ColumnKind::Index => index.to_string(),
+                    ColumnKind::Size => state.size_format.format(size),
+                    ColumnKind::Disk => state.size_format.format(item.node.disk_size()),
+                    ColumnKind::Percent => format!("{:.1}%", fraction * 100.0),
+                    ColumnKind::Items => match item.node.kind() {
+                        Directory(_) => {
+                            let (dirs, files) = item.node.counts();
+                            (dirs + files).to_string()
+                        }
+                        File => String::new(),
+                    },
+                    ColumnKind::Mtime => item.node.modified().map_or("-".to_string(), |t| {
+                        format_local_time(t).chars().take(16).collect()
+                    }),
+                    ColumnKind::Owner => meta.as_ref().map_or(String::new(), user),
+                    ColumnKind::Permissions => meta.as_ref().map_or(String::new(), mode_string),
+                    ColumnKind::Extension => match item.node.kind() {
+                        File => item
+                            .node
+                            .path()
+                            .extension()
+                            .map_or(String::new(), |ext| ext.to_string_lossy().into_owned()),
+                        Directory(_) => String::new(),
+                    },
+                };
+                fit(&text, width, align)
+            })
+            .collect();
+
+        lines.push(format!(
+            "{}{}{}{}{}",
             hl_start,
             selection,
-            idx_str,
-            prefix,
-            icon,
-            name_color,
-            item.node
-                .path()
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy(),
-            fg_reset,
-            size_str,
+            mark,
+            cells.join(" ").trim_end(),
             hl_end
-        );
+        ));
+    }
+
+    // 详情面板固定在列表下方
+    if state.show_details {
+        lines.extend(std::iter::repeat_n(String::new(), height - (end - start)));
+        lines.push("--- Details ---".to_string());
+        if let Some(item) = view.get(cursor) {
+            for (label, value) in describe(&state.root, item.node, state.size_format) {
+                lines.push(format!("{:<9} {}", format!("{label}:"), value));
+            }
+        }
     }
 
     // 状态栏
@@ -152,33 +357,124 @@ pub fn render(state: &UiState) {
             String::new()
         };
         let reset = state.theme.reset.to_ansi().unwrap_or_default();
-        let _ = write!(out, "{}{}{}\r\n", color, status.text, reset);
+        lines.push(format!("{}{}{}", color, status.text, reset));
+    } else if !state.marked.is_empty() {
+        let (count, size) = state.marked_total();
+        lines.push(format!(
+            "{count} marked, {} total ([u] clear, [d]/[x] remove, [w] write list)",
+            state.size_format.format(size)
+        ));
     } else {
-        let _ = write!(out, "\r\n");
+        lines.push(String::new());
     }
 
-    // 帮助栏
+    // 帮助栏（输入框打开时显示输入框）
+    if let Some(prompt) = &state.prompt {
+        let label = match prompt.kind {
+            PromptKind::Search => "/",
+            PromptKind::Filter => "Filter: ",
+            PromptKind::Confirm => "",
+            PromptKind::Export => "Write marked paths to: ",
+            PromptKind::Columns => "Toggle columns: ",
+        };
+        lines.push(format!("{}{}", label, prompt.text));
+        return lines;
+    }
     let sort_label = match state.sort_mode {
         SortMode::NameAsc => "name",
         SortMode::SizeDesc => "size",
     };
-    let _ = write!(
-        out,
-        "[j/k] Move | [Enter/t] Toggle | [s] Sort({}) | [q] Quit | Index: {} > ",
+    lines.push(format!(
+        "[j/k] Move | [h/l] Up/Into | [Enter/t] Toggle | [>/<] Zoom | [/] Search | [f] Filter | [s] Sort({}) | [q] Quit | Index: {} > ",
         sort_label, state.input_buffer
-    );
-    let _ = out.flush();
+    ));
+    lines
 }
 
-/// 格式化文件大小
-fn format_size(size: u64) -> String {
-    if size < 1024 {
-        format!("{} B", size)
-    } else if size < 1024 * 1024 {
-        format!("{:.1} KB", size as f64 / 1024.0)
-    } else {
-        format!("{:.1} MB", size as f64 / 1024.0 / 1024.0)
+/// 扫描根列表（含各自大小）
+fn roots_line(root: &Node, format: SizeFormat) -> String {
+    match root.kind() {
+        Directory(prop) if root.is_synthetic() => {
+            let roots: Vec<String> = prop
+                .children()
+                .iter()
+                .map(|r| format!("{} ({})", r.path().display(), format.format(r.size())))
+                .collect();
+            format!("Roots: {}", roots.join(", "))
+        }
+        _ => format!("Root: {}", root.path().display()),
+    }
+}
+
+/// 缩放后的面包屑：扫描根完整路径，其后逐级列出目录名
+fn breadcrumb(root: &Node, view_root: &Node) -> String {
+    let scan_root = match root.kind() {
+        Directory(prop) if root.is_synthetic() => prop
+            .children()
+            .iter()
+            .find(|r| view_root.path().starts_with(r.path()))
+            .unwrap_or(root),
+        _ => root,
+    };
+    let relative = view_root
+        .path()
+        .strip_prefix(scan_root.path())
+        .unwrap_or(view_root.path());
+    let mut crumbs = vec![scan_root.path().display().to_string()];
+    crumbs.extend(relative.iter().map(|c| c.to_string_lossy().into_owned()));
+    format!("Zoom: {}  (< to go back)", crumbs.join(" › "))
+}
+
+/// 行显示名：扫描根显示完整路径，合成根显示根数量
+fn display_name(root: &Node, item: &ViewItem<'_>) -> String {
+    if let Directory(prop) = root.kind()
+        && root.is_synthetic()
+    {
+        if std::ptr::eq(item.node, root) {
+            return format!("({} roots)", prop.children().len());
+        }
+        if prop.children().iter().any(|r| std::ptr::eq(r, item.node)) {
+            return item.node.path().display().to_string();
+        }
+    }
+    item.node
+        .path()
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// 各行占比的基准大小：父目录（视图中上一层的行）或显示根的大小
+///
+/// 显示根没有父行，以自身为基准。
+fn share_bases(view: &[ViewItem<'_>], base: ShareBase) -> Vec<u64> {
+    let root_size = view.first().map_or(0, |item| item.size);
+    let mut ancestors: Vec<u64> = Vec::new();
+    view.iter()
+        .map(|item| {
+            ancestors.truncate(item.depth);
+            let parent = ancestors.last().copied().unwrap_or(item.size);
+            ancestors.push(item.size);
+            match base {
+                ShareBase::Parent => parent,
+                ShareBase::Root => root_size,
+            }
+        })
+        .collect()
+}
+
+/// 占比条：`width` 个字符宽，以八分之一块字符细分
+fn bar(fraction: f64, width: usize) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let eighths = (fraction.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    if !eighths.is_multiple_of(8) {
+        bar.push(PARTIAL[eighths % 8]);
     }
+    let filled = bar.chars().count();
+    bar.push_str(&" ".repeat(width - filled));
+    format!("[{bar}]")
 }
 
 /// 计算大小范围
@@ -191,7 +487,7 @@ fn size_range(view: &[ViewItem<'_>], want_dir: bool) -> Option<(u64, u64)> {
         if is_dir != want_dir {
             continue;
         }
-        let size = item.node.size();
+        let size = item.size;
         min = Some(min.map_or(size, |m| m.min(size)));
         max = Some(max.map_or(size, |m| m.max(size)));
     }
@@ -231,26 +527,96 @@ fn lerp_rgb(s: (u8, u8, u8), e: (u8, u8, u8), t: f64) -> (u8, u8, u8) {
     (lerp(s.0, e.0), lerp(s.1, e.1), lerp(s.2, e.2))
 }
 
+/// 当前终端尺寸对应的动作，用于初始化视口
+pub fn resize_action() -> anyhow::Result<Action> {
+    let (cols, rows) = terminal::size()?;
+    Ok(Action::Resize(cols, rows))
+}
+
 /// 读取用户输入
-pub fn get_input() -> anyhow::Result<Action> {
+///
+/// `in_prompt` 为真时按键作为输入框文本处理；`commands` 中的按键优先于内置按键。
+pub fn get_input(in_prompt: bool, commands: &[CustomCommand]) -> anyhow::Result<Action> {
     loop {
-        if let Event::Key(key) = event::read()? {
+        let event = event::read()?;
+        if let Event::Resize(cols, rows) = event {
+            return Ok(Action::Resize(cols, rows));
+        }
+        if let Event::Key(key) = event {
             if key.kind == KeyEventKind::Release {
                 continue;
             }
 
             use KeyCode::*;
+            if in_prompt {
+                match key.code {
+                    Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(Action::Quit);
+                    }
+                    Esc => return Ok(Action::PromptCancel),
+                    Enter => return Ok(Action::PromptSubmit),
+                    Backspace => return Ok(Action::PromptBackspace),
+                    Char(ch) => return Ok(Action::PromptInput(ch)),
+                    _ => continue,
+                }
+            }
+            if key.modifiers.contains(KeyModifiers::CONTROL) {
+                match key.code {
+                    Char('c') => return Ok(Action::Quit),
+                    Char('d') => return Ok(Action::HalfPageDown),
+                    Char('u') => return Ok(Action::HalfPageUp),
+                    _ => {}
+                }
+            }
+            // 自定义命令只响应不带修饰键（Shift 除外）的按键
+            if let Char(ch) = key.code
+                && key.modifiers.difference(KeyModifiers::SHIFT).is_empty()
+                && let Some(index) = commands.iter().position(|c| c.key == ch)
+            {
+                return Ok(Action::Custom(index));
+            }
             match key.code {
                 Up | Char('k' | 'K') => return Ok(Action::MoveUp),
                 Down | Char('j' | 'J') => return Ok(Action::MoveDown),
+                Left | Char('h') => return Ok(Action::CollapseOrParent),
+                Right | Char('l') => return Ok(Action::ExpandOrChild),
                 Enter => return Ok(Action::Enter),
                 Backspace => return Ok(Action::InputBackspace),
                 Char('q' | 'Q') => return Ok(Action::Quit),
-                Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    return Ok(Action::Quit);
-                }
+                PageUp => return Ok(Action::PageUp),
+                PageDown => return Ok(Action::PageDown),
+                Home | Char('g') => return Ok(Action::Home),
+                End | Char('G') => return Ok(Action::End),
                 Char('t' | 'T') => return Ok(Action::ToggleAtCursor),
+                Char('E') => return Ok(Action::ExpandSubtree),
+                Char('e') => return Ok(Action::ExpandToDepth),
+                Char('C') => return Ok(Action::CollapseAll),
                 Char('s' | 'S') => return Ok(Action::ToggleSort),
+                Char('/') => return Ok(Action::StartSearch),
+                Char('n') => return Ok(Action::NextMatch),
+                Char('N') => return Ok(Action::PrevMatch),
+                Char('>') => return Ok(Action::ZoomIn),
+                Char('<') => return Ok(Action::ZoomOut),
+                Char(' ') => return Ok(Action::ToggleMark),
+                Char('v') => return Ok(Action::InvertMarks),
+                Char('a') => return Ok(Action::MarkChildren),
+                Char('u') => return Ok(Action::ClearMarks),
+                Char('w') => return Ok(Action::ExportMarks),
+                Char('!') => return Ok(Action::Spawn(Program::Shell)),
+                Char('o') => return Ok(Action::Spawn(Program::Editor)),
+                Char('p') => return Ok(Action::Spawn(Program::Pager)),
+                Char('O') => return Ok(Action::Spawn(Program::Open)),
+                Char('y') => return Ok(Action::CopyPath),
+                Char('d') => return Ok(Action::Delete),
+                Char('x') => return Ok(Action::Trash),
+                Char('f') => return Ok(Action::StartFilter),
+                Char('F') => return Ok(Action::ToggleFilterSizes),
+                Char('i') => return Ok(Action::ToggleDetails),
+                Char('%') => return Ok(Action::TogglePercent),
+                Char('b') => return Ok(Action::ToggleBar),
+                Char('B') => return Ok(Action::ToggleShareBase),
+                Char('U') => return Ok(Action::CycleUnits),
+                Char('c') => return Ok(Action::StartColumns),
                 Char(ch) if ch.is_ascii_digit() => return Ok(Action::InputDigit(ch)),
                 _ => {}
             }
@@ -2,31 +2,174 @@
 //!
 //! 本模块提供了 [`UiState`]，用于跟踪节点的展开状态，并将层次树结构投影到线性列表中以便渲染。
 
-use crate::model::{Node, NodeKind::*};
+use crate::clipboard;
+use crate::columns::{ColumnKind, toggle_column};
+use crate::config::Config;
+use crate::delete::{Failure, remove_tree};
+use crate::details::DETAILS_LINES;
+use crate::filter::Filter;
+use crate::model::{Node, NodeKind::*, ScanOptions};
+use crate::pattern::Matcher;
+use crate::size::SizeFormat;
+use crate::spawn::{Program, Spawn, expand_command, parent_dir, run_captured};
 use crate::theme::Theme;
-use anyhow::bail;
+use crate::trash::move_to_trash;
+use anyhow::{Context, bail};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
 
 /// UI动作
 #[allow(dead_code)]
 pub enum Action {
-    Toggle(usize),      // 按索引切换
-    ToggleAtCursor,     // 切换光标处
-    MoveUp,             // 上移
-    MoveDown,           // 下移
-    Enter,              // 确认
-    InputDigit(char),   // 输入数字
-    InputBackspace,     // 退格
-    ToggleSort,         // 切换排序
-    Quit,               // 退出
+    Toggle(usize),     // 按索引切换
+    ToggleAtCursor,    // 切换光标处
+    CollapseOrParent,  // 折叠光标处目录，已折叠时跳到父目录
+    ExpandOrChild,     // 展开光标处目录并进入第一个子项
+    ExpandSubtree,     // 展开光标处目录的整棵子树
+    CollapseAll,       // 折叠除根以外的所有目录
+    ExpandToDepth,     // 展开到计数前缀指定的深度（默认 1）
+    MoveUp,            // 上移
+    MoveDown,          // 下移
+    PageUp,            // 上翻一页
+    PageDown,          // 下翻一页
+    HalfPageUp,        // 上翻半页
+    HalfPageDown,      // 下翻半页
+    Home,              // 跳到首行
+    End,               // 跳到末行（有计数前缀时跳到该索引）
+    Enter,             // 确认
+    InputDigit(char),  // 输入数字
+    InputBackspace,    // 退格
+    ToggleSort,        // 切换排序
+    StartSearch,       // 打开搜索输入
+    NextMatch,         // 下一个匹配
+    PrevMatch,         // 上一个匹配
+    ZoomIn,            // 以光标处目录为显示根
+    ZoomOut,           // 返回上一个显示根
+    StartFilter,       // 打开过滤输入
+    ToggleFilterSizes, // 切换过滤后的大小统计方式
+    ToggleDetails,     // 显示或隐藏详情面板
+    TogglePercent,     // 显示或隐藏占比列
+    ToggleBar,         // 显示或隐藏占比条
+    StartColumns,      // 打开切换列的输入框
+    ToggleShareBase,   // 占比在父目录与显示根之间切换
+    CycleUnits,        // 切换大小单位制（IEC → SI → 字节）
+    Delete,            // 删除光标处或标记的条目（需确认）
+    Trash,             // 将光标处或标记的条目移到回收站（需确认）
+    ToggleMark,        // 切换光标处条目的标记
+    InvertMarks,       // 反转可见行的标记
+    MarkChildren,      // 标记光标处目录的所有子项
+    ClearMarks,        // 清除所有标记
+    ExportMarks,       // 将标记的路径导出到文件
+    Spawn(Program),    // 对光标处条目运行外部程序
+    CopyPath,          // 复制光标处或标记条目的完整路径
+    Custom(usize),     // 对光标处条目运行配置中的自定义命令（按序号）
+    PromptInput(char), // 输入框输入字符
+    PromptBackspace,   // 输入框退格
+    PromptSubmit,      // 输入框确认
+    PromptCancel,      // 输入框取消
+    Resize(u16, u16),  // 终端尺寸变化（列, 行）
+    Quit,              // 退出
 }
 
-/// 排序模式
+/// 列表以外占用的行数（标题、根路径、滚动提示、状态栏、帮助栏）
+const CHROME_LINES: usize = 5;
+
+/// 展开后可见行数超过此值时需要再次确认
+const EXPAND_WARN_ROWS: usize = 5000;
+
+/// 需要确认的展开操作
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ExpandTarget {
+    Subtree(PathBuf),
+    Depth(usize),
+}
+
+/// 移除方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Removal {
+    Delete, // 永久删除
+    Trash,  // 移到回收站
+}
+
+/// 输入框类型
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromptKind {
+    Search,  // 搜索
+    Filter,  // 过滤
+    Confirm, // 确认（`y` 确认，其他键取消），文本为提示问题
+    Export,  // 导出标记列表的文件名
+    Columns, // 要切换显示的列名
+}
+
+/// 输入框
+#[derive(Clone, Debug)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub text: String,
+}
+
+/// 搜索状态
+///
+/// 匹配项覆盖显示根下的整棵子树（不限于可见行），按当前排序下的先序顺序排列。
+pub struct Search {
+    pub matches: Vec<PathBuf>,         // 匹配节点
+    pub matched: HashSet<PathBuf>,     // 匹配路径，用于高亮
+    pub current: Option<usize>,        // 当前匹配
+    origin_cursor: usize,              // 开始搜索时的光标
+    origin_expanded: HashSet<PathBuf>, // 开始搜索时的展开状态
+}
+
+/// 缩放前的视图，返回时恢复
+struct ZoomFrame {
+    root: PathBuf,              // 显示根
+    cursor: usize,              // 光标
+    expanded: HashSet<PathBuf>, // 展开状态
+}
+
+/// 生效中的过滤
+pub struct ActiveFilter {
+    pub filter: Filter,             // 过滤表达式
+    visible: HashMap<PathBuf, u64>, // 可见节点及其只统计匹配项的大小
+}
+
+/// 排序模式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum SortMode {
-    NameAsc,  // 按名称升序
+    NameAsc, // 按名称升序
+    #[default]
     SizeDesc, // 按大小降序
 }
 
+/// 占比基准
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShareBase {
+    #[default]
+    Parent, // 父目录
+    Root, // 显示根
+}
+
+/// 比较节点（目录优先，再按排序模式）
+pub(crate) fn compare_nodes(a: &Node, b: &Node, sort_mode: SortMode) -> std::cmp::Ordering {
+    compare_sized((a, a.size()), (b, b.size()), sort_mode)
+}
+
+/// 按给定大小比较节点（目录优先，再按排序模式）
+fn compare_sized(
+    (a, a_size): (&Node, u64),
+    (b, b_size): (&Node, u64),
+    sort_mode: SortMode,
+) -> std::cmp::Ordering {
+    match (a.kind(), b.kind()) {
+        (Directory(_), File) => std::cmp::Ordering::Less,
+        (File, Directory(_)) => std::cmp::Ordering::Greater,
+        _ => match sort_mode {
+            SortMode::NameAsc => a.path().cmp(b.path()),
+            SortMode::SizeDesc => b_size.cmp(&a_size).then_with(|| a.path().cmp(b.path())),
+        },
+    }
+}
+
 /// 状态消息
 #[derive(Clone, Debug)]
 pub struct StatusMessage {
@@ -38,50 +181,115 @@ pub struct StatusMessage {
 pub struct ViewItem<'a> {
     pub node: &'a Node,
     pub depth: usize,
+    pub size: u64, // 显示大小（过滤时可能只统计匹配项）
 }
 
 /// UI状态
-pub struct UiState<'a> {
-    pub root: &'a Node,
-    pub expanded_nodes: Vec<&'a Node>, // 已展开节点
-    pub cursor: usize,                 // 光标位置
-    pub viewport_height: usize,        // 视口高度
-    pub input_buffer: String,          // 输入缓冲
-    pub status: Option<StatusMessage>, // 状态消息
-    pub theme: Theme,                  // 主题
-    pub sort_mode: SortMode,           // 排序模式
-}
-
-impl<'a> UiState<'a> {
+pub struct UiState {
+    pub root: Node,                                   // 扫描根
+    view_root: PathBuf,                               // 显示根（缩放后为子目录）
+    pub expanded_nodes: HashSet<PathBuf>,             // 已展开节点（按路径）
+    pub cursor: usize,                                // 光标位置
+    pub viewport_height: usize,                       // 视口高度
+    pub terminal_size: (u16, u16),                    // 终端尺寸（列, 行）
+    pub show_details: bool,                           // 显示选中条目的详情面板
+    pub share_base: ShareBase,                        // 占比基准
+    pub input_buffer: String,                         // 输入缓冲
+    pub status: Option<StatusMessage>,                // 状态消息
+    pub config: Config,                               // 用户配置
+    pub theme: Theme,                                 // 主题
+    pub size_format: SizeFormat,                      // 大小格式
+    pub sort_mode: SortMode,                          // 排序模式
+    pub prompt: Option<Prompt>,                       // 输入框
+    pub search: Option<Search>,                       // 搜索
+    pub filter: Option<ActiveFilter>,                 // 过滤
+    pub filtered_sizes: bool,                         // 过滤时目录大小只统计匹配项
+    filter_origin: Option<String>,                    // 打开过滤输入前的表达式，取消时恢复
+    pub marked: BTreeSet<PathBuf>,                    // 标记的条目
+    pub spawn: Option<Spawn>,                         // 等待主循环运行的外部程序
+    pub scan_options: Option<ScanOptions>,            // 重新扫描所用选项，路径列表模式下不重新扫描
+    pub dry_run: bool,                                // 只报告删除或移到回收站会移除的内容
+    zoom_stack: Vec<ZoomFrame>,                       // 缩放返回栈
+    pending_expand: Option<ExpandTarget>,             // 等待再次确认的大量展开
+    pending_removal: Option<(Removal, Vec<PathBuf>)>, // 等待确认移除的条目
+}
+
+impl UiState {
     /// 创建新状态，默认展开根节点
-    pub fn new(root: &'a Node, theme: Theme) -> Self {
+    pub fn new(root: Node, theme: Theme, sort_mode: SortMode) -> Self {
         Self {
+            view_root: root.path().to_path_buf(),
+            expanded_nodes: HashSet::from([root.path().to_path_buf()]),
             root,
-            expanded_nodes: vec![root],
             cursor: 0,
             viewport_height: 20,
+            terminal_size: (80, 24),
+            show_details: false,
+            share_base: ShareBase::default(),
             input_buffer: String::new(),
             status: None,
+            config: Config::default(),
             theme,
-            sort_mode: SortMode::SizeDesc,
+            size_format: SizeFormat::default(),
+            sort_mode,
+            prompt: None,
+            search: None,
+            filter: None,
+            filtered_sizes: true,
+            filter_origin: None,
+            marked: BTreeSet::new(),
+            spawn: None,
+            scan_options: None,
+            dry_run: false,
+            zoom_stack: Vec::new(),
+            pending_expand: None,
+            pending_removal: None,
         }
     }
 
+    /// 显示根节点（缩放的目录不存在时回到扫描根）
+    pub fn view_root(&self) -> &Node {
+        self.root.find(&self.view_root).unwrap_or(&self.root)
+    }
+
+    /// 按终端高度与详情面板计算视口高度
+    ///
+    /// 渲染时按光标计算滚动范围，光标始终可见。
+    fn update_viewport(&mut self) {
+        let panel = if self.show_details { DETAILS_LINES } else { 0 };
+        self.viewport_height = (self.terminal_size.1 as usize)
+            .saturating_sub(CHROME_LINES + panel)
+            .max(1);
+    }
+
     /// 展平树为可见项列表
-    pub fn flatten_view(&self) -> Vec<ViewItem<'a>> {
+    pub fn flatten_view(&self) -> Vec<ViewItem<'_>> {
         let mut items = Vec::new();
-        self.collect_recursive(self.root, 0, &mut items);
+        self.collect_recursive(self.view_root(), 0, &mut items);
         items
     }
 
     /// 递归收集可见节点
-    fn collect_recursive(&self, node: &'a Node, depth: usize, items: &mut Vec<ViewItem<'a>>) {
-        items.push(ViewItem { node, depth });
+    fn collect_recursive<'s>(
+        &'s self,
+        node: &'s Node,
+        depth: usize,
+        items: &mut Vec<ViewItem<'s>>,
+    ) {
+        items.push(ViewItem {
+            node,
+            depth,
+            size: self.display_size(node),
+        });
 
         if let Directory(prop) = node.kind()
-            && self.expanded_nodes.contains(&node)
+            && self.expanded_nodes.contains(node.path())
         {
-            let mut children: Vec<&Node> = prop.children().iter().collect();
+            let mut children: Vec<&Node> = prop
+                .children()
+                .iter()
+                .filter(|c| self.is_visible(c))
+                .collect();
             children.sort_by(|a, b| self.compare_nodes(a, b));
             for child in children {
                 self.collect_recursive(child, depth + 1, items);
@@ -89,18 +297,30 @@ impl<'a> UiState<'a> {
         }
     }
 
-    /// 比较节点（目录优先，再按排序模式）
-    fn compare_nodes(&self, a: &Node, b: &Node) -> std::cmp::Ordering {
-        match (a.kind(), b.kind()) {
-            (Directory(_), File) => std::cmp::Ordering::Less,
-            (File, Directory(_)) => std::cmp::Ordering::Greater,
-            _ => match self.sort_mode {
-                SortMode::NameAsc => a.path().cmp(b.path()),
-                SortMode::SizeDesc => b.size().cmp(&a.size()).then_with(|| a.path().cmp(b.path())),
-            },
+    /// 节点是否通过过滤
+    fn is_visible(&self, node: &Node) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|f| f.visible.contains_key(node.path()))
+    }
+
+    /// 节点的显示大小
+    fn display_size(&self, node: &Node) -> u64 {
+        match &self.filter {
+            Some(f) if self.filtered_sizes => f.visible.get(node.path()).copied().unwrap_or(0),
+            _ => node.size(),
         }
     }
 
+    /// 比较节点（目录优先，再按排序模式和显示大小）
+    fn compare_nodes(&self, a: &Node, b: &Node) -> std::cmp::Ordering {
+        compare_sized(
+            (a, self.display_size(a)),
+            (b, self.display_size(b)),
+            self.sort_mode,
+        )
+    }
+
     /// 移动光标
     fn move_cursor(&mut self, delta: isize, view_len: usize) {
         if view_len == 0 {
@@ -115,11 +335,872 @@ impl<'a> UiState<'a> {
         self.cursor = new_cursor;
     }
 
+    /// 取出输入缓冲中的计数前缀（默认 1）
+    fn take_count(&mut self) -> usize {
+        let count = self.input_buffer.parse().unwrap_or(1);
+        self.input_buffer.clear();
+        count
+    }
+
+    /// 按计数前缀重复移动，`step` 为单次移动的行数
+    fn move_with_count(&mut self, step: isize, view_len: usize) {
+        // 超过视图长度的计数等同于移到头尾，先截断以免转换为 isize 时溢出
+        let count = self.take_count().clamp(1, view_len.max(1));
+        self.clear_status();
+        self.move_cursor(step.saturating_mul(count as isize), view_len);
+    }
+
     /// 切换光标处目录
     fn toggle_at_cursor(&mut self) -> anyhow::Result<()> {
         self.toggle_by_index(self.cursor)
     }
 
+    /// 折叠光标处已展开的目录，否则跳到父目录
+    fn collapse_or_parent(&mut self) -> anyhow::Result<()> {
+        let view = self.flatten_view();
+        let Some(item) = view.get(self.cursor) else {
+            return Ok(());
+        };
+        let path = item.node.path().to_path_buf();
+        // 父目录是向上第一个深度更小的项
+        let parent = view[..self.cursor]
+            .iter()
+            .rposition(|v| v.depth + 1 == item.depth);
+        let depth = item.depth;
+
+        if self.expanded_nodes.remove(&path) {
+            return Ok(());
+        }
+        if depth == 0 {
+            bail!("Already at the top");
+        }
+        if let Some(parent) = parent {
+            self.cursor = parent;
+        }
+        Ok(())
+    }
+
+    /// 展开光标处目录并进入第一个子项
+    fn expand_or_child(&mut self) -> anyhow::Result<()> {
+        let view = self.flatten_view();
+        let Some(item) = view.get(self.cursor) else {
+            return Ok(());
+        };
+        let Directory(prop) = item.node.kind() else {
+            bail!("Cannot expand file");
+        };
+        let path = item.node.path().to_path_buf();
+        // 过滤时只看可见的子项，全部被隐藏时展开后没有可进入的子项
+        let has_children = prop.children().iter().any(|c| self.is_visible(c));
+
+        self.expanded_nodes.insert(path);
+        if has_children {
+            self.cursor += 1;
+        }
+        Ok(())
+    }
+
+    /// 展开整棵子树或展开到指定深度
+    ///
+    /// 展开后可见行数超过 [`EXPAND_WARN_ROWS`] 时先恢复原状并提示，再次执行同一操作才生效。
+    fn expand(&mut self, target: ExpandTarget, confirmed: bool) -> anyhow::Result<()> {
+        let previous = self.expanded_nodes.clone();
+        match &target {
+            ExpandTarget::Subtree(path) => {
+                if let Some(node) = self.root.find(path) {
+                    collect_dirs(node, usize::MAX, &mut self.expanded_nodes);
+                }
+            }
+            ExpandTarget::Depth(depth) => {
+                let view_root = self.root.find(&self.view_root).unwrap_or(&self.root);
+                self.expanded_nodes.clear();
+                collect_dirs(view_root, *depth, &mut self.expanded_nodes);
+                self.expanded_nodes.insert(self.view_root.clone());
+            }
+        }
+
+        let rows = self.flatten_view().len();
+        if rows > EXPAND_WARN_ROWS && !confirmed {
+            self.expanded_nodes = previous;
+            self.pending_expand = Some(target);
+            bail!("This would show {rows} rows; repeat to expand anyway");
+        }
+        if self.cursor >= rows {
+            self.cursor = rows.saturating_sub(1);
+        }
+        Ok(())
+    }
+
+    /// 折叠除根以外的所有目录，光标回到根
+    fn collapse_all(&mut self) {
+        self.expanded_nodes.clear();
+        self.expanded_nodes.insert(self.view_root.clone());
+        self.cursor = 0;
+    }
+
+    /// 以光标处目录为显示根，当前视图入栈
+    fn zoom_in(&mut self) -> anyhow::Result<()> {
+        let view = self.flatten_view();
+        let Some(item) = view.get(self.cursor) else {
+            return Ok(());
+        };
+        if !item.node.kind().is_dir() {
+            bail!("Cannot zoom into file");
+        }
+        if item.depth == 0 {
+            bail!("Already the displayed root");
+        }
+        let path = item.node.path().to_path_buf();
+        self.zoom_stack.push(ZoomFrame {
+            root: std::mem::replace(&mut self.view_root, path.clone()),
+            cursor: self.cursor,
+            expanded: self.expanded_nodes.clone(),
+        });
+        self.expanded_nodes.insert(path);
+        self.cursor = 0;
+        self.search = None;
+        Ok(())
+    }
+
+    /// 返回上一个显示根，恢复光标与展开状态
+    fn zoom_out(&mut self) -> anyhow::Result<()> {
+        let Some(frame) = self.zoom_stack.pop() else {
+            bail!("Not zoomed in");
+        };
+        self.view_root = frame.root;
+        self.expanded_nodes = frame.expanded;
+        self.cursor = frame
+            .cursor
+            .min(self.flatten_view().len().saturating_sub(1));
+        self.search = None;
+        Ok(())
+    }
+
+    /// 按当前排序列出显示根下的先序节点
+    fn preorder(&self) -> Vec<&Node> {
+        let mut nodes = Vec::new();
+        self.collect_preorder(self.view_root(), &mut nodes);
+        nodes
+    }
+
+    /// 递归收集先序节点
+    fn collect_preorder<'s>(&'s self, node: &'s Node, nodes: &mut Vec<&'s Node>) {
+        nodes.push(node);
+        if let Directory(prop) = node.kind() {
+            let mut children: Vec<&Node> = prop.children().iter().collect();
+            children.sort_by(|a, b| self.compare_nodes(a, b));
+            for child in children {
+                self.collect_preorder(child, nodes);
+            }
+        }
+    }
+
+    /// 展开目标节点的所有祖先，并将光标移到该节点
+    fn reveal(&mut self, target: &Path) {
+        // 显示根到目标之间的各级目录
+        let ancestors: Vec<PathBuf> = target
+            .ancestors()
+            .skip(1)
+            .take_while(|p| p.starts_with(&self.view_root))
+            .map(Path::to_path_buf)
+            .collect();
+        self.expanded_nodes.extend(ancestors);
+        if let Some(index) = self
+            .flatten_view()
+            .iter()
+            .position(|v| v.node.path() == target)
+        {
+            self.cursor = index;
+        }
+    }
+
+    /// 打开搜索输入，记录当前位置以便取消时恢复
+    fn start_search(&mut self) {
+        self.prompt = Some(Prompt {
+            kind: PromptKind::Search,
+            text: String::new(),
+        });
+        self.search = Some(Search {
+            matches: Vec::new(),
+            matched: HashSet::new(),
+            current: None,
+            origin_cursor: self.cursor,
+            origin_expanded: self.expanded_nodes.clone(),
+        });
+    }
+
+    /// 按输入框内容重新搜索，并跳到起点之后的第一个匹配
+    fn update_search(&mut self) -> anyhow::Result<()> {
+        let Some(prompt) = &self.prompt else {
+            return Ok(());
+        };
+        let Some(search) = &mut self.search else {
+            return Ok(());
+        };
+        // 每次输入都从起点重新开始，避免中间结果把树展开得过多
+        self.cursor = search.origin_cursor;
+        self.expanded_nodes = search.origin_expanded.clone();
+        search.matches.clear();
+        search.matched.clear();
+        search.current = None;
+        if prompt.text.is_empty() {
+            return Ok(());
+        }
+
+        let matcher = Matcher::parse(&prompt.text)?;
+        let view = self.flatten_view();
+        let origin = view.get(self.cursor).map(|v| v.node);
+        let nodes = self.preorder();
+        let origin_pos = origin
+            .and_then(|o| nodes.iter().position(|n| std::ptr::eq(*n, o)))
+            .unwrap_or(0);
+
+        let mut matches = Vec::new();
+        let mut current = None;
+        for (pos, node) in nodes.iter().enumerate() {
+            if node.is_synthetic() || !self.is_visible(node) {
+                continue;
+            }
+            let name = node
+                .path()
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            if matcher.is_match(&name) {
+                if current.is_none() && pos > origin_pos {
+                    current = Some(matches.len());
+                }
+                matches.push(node.path().to_path_buf());
+            }
+        }
+        // 起点之后没有匹配时回绕到第一个
+        let current = current.or((!matches.is_empty()).then_some(0));
+
+        let Some(search) = &mut self.search else {
+            return Ok(());
+        };
+        search.matched = matches.iter().cloned().collect();
+        search.matches = matches;
+        search.current = current;
+        if let Some(index) = current {
+            let target = search.matches[index].clone();
+            self.reveal(&target);
+        }
+        Ok(())
+    }
+
+    /// 跳到下一个（或上一个）匹配
+    fn jump_to_match(&mut self, forward: bool) -> anyhow::Result<()> {
+        let Some(search) = &mut self.search else {
+            bail!("No active search (press / to search)");
+        };
+        let len = search.matches.len();
+        if len == 0 {
+            bail!("No matches");
+        }
+        let next = match (search.current, forward) {
+            (None, _) => 0,
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+        };
+        search.current = Some(next);
+        let target = search.matches[next].clone();
+        self.reveal(&target);
+        self.set_match_status();
+        Ok(())
+    }
+
+    /// 在状态栏显示匹配进度
+    fn set_match_status(&mut self) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let text = match search.current {
+            Some(i) => format!("Match {}/{} (n/N to cycle)", i + 1, search.matches.len()),
+            None => "No matches".to_string(),
+        };
+        self.status = Some(StatusMessage {
+            text,
+            is_error: search.matches.is_empty(),
+        });
+    }
+
+    /// 打开过滤输入，预填当前表达式
+    fn start_filter(&mut self) {
+        let text = self
+            .filter
+            .as_ref()
+            .map(|f| f.filter.text.clone())
+            .unwrap_or_default();
+        self.filter_origin = Some(text.clone());
+        self.prompt = Some(Prompt {
+            kind: PromptKind::Filter,
+            text,
+        });
+    }
+
+    /// 应用过滤表达式（空表达式清除过滤），尽量保持光标所在节点
+    fn apply_filter(&mut self, text: &str) -> anyhow::Result<()> {
+        let filter = if text.trim().is_empty() {
+            None
+        } else {
+            Some(Filter::parse(text)?)
+        };
+        let selected = self
+            .flatten_view()
+            .get(self.cursor)
+            .map(|v| v.node.path().to_path_buf());
+
+        self.filter = filter.map(|filter| ActiveFilter {
+            visible: filter.visible(&self.root),
+            filter,
+        });
+
+        let view = self.flatten_view();
+        let cursor = selected
+            .and_then(|path| view.iter().position(|v| v.node.path() == path))
+            .unwrap_or(self.cursor.min(view.len().saturating_sub(1)));
+        self.cursor = cursor;
+        if self.filter.as_ref().is_some_and(|f| f.visible.len() <= 1) {
+            bail!("No entries match the filter");
+        }
+        Ok(())
+    }
+
+    /// 是否为扫描根（含合成根及其下的各扫描根）
+    fn is_scan_root(&self, path: &Path) -> bool {
+        path == self.root.path()
+            || match self.root.kind() {
+                Directory(prop) if self.root.is_synthetic() => {
+                    prop.children().iter().any(|r| r.path() == path)
+                }
+                _ => false,
+            }
+    }
+
+    /// 包含路径的扫描根
+    fn scan_root_of(&self, path: &Path) -> Option<&Path> {
+        match self.root.kind() {
+            Directory(prop) if self.root.is_synthetic() => prop
+                .children()
+                .iter()
+                .map(|r| r.path())
+                .find(|r| path.starts_with(r)),
+            _ => Some(self.root.path()).filter(|r| path.starts_with(r)),
+        }
+    }
+
+    /// 切换光标处条目的标记，并下移一行
+    fn toggle_mark(&mut self) {
+        let view = self.flatten_view();
+        let Some(item) = view.get(self.cursor) else {
+            return;
+        };
+        let path = item.node.path().to_path_buf();
+        let view_len = view.len();
+        if !self.marked.remove(&path) {
+            self.marked.insert(path);
+        }
+        self.move_cursor(1, view_len);
+    }
+
+    /// 反转所有可见行（显示根除外）的标记
+    fn invert_marks(&mut self) {
+        let paths: Vec<PathBuf> = self
+            .flatten_view()
+            .iter()
+            .filter(|v| v.depth > 0)
+            .map(|v| v.node.path().to_path_buf())
+            .collect();
+        for path in paths {
+            if !self.marked.remove(&path) {
+                self.marked.insert(path);
+            }
+        }
+    }
+
+    /// 标记光标处目录的所有（通过过滤的）子项
+    fn mark_children(&mut self) -> anyhow::Result<()> {
+        let view = self.flatten_view();
+        let Some(item) = view.get(self.cursor) else {
+            return Ok(());
+        };
+        let Directory(prop) = item.node.kind() else {
+            bail!("Cannot mark children of a file");
+        };
+        let children: Vec<PathBuf> = prop
+            .children()
+            .iter()
+            .filter(|c| self.is_visible(c))
+            .map(|c| c.path().to_path_buf())
+            .collect();
+        self.marked.extend(children);
+        Ok(())
+    }
+
+    /// 去除嵌套后的标记条目（祖先已标记的条目不重复计算）
+    pub fn marked_roots(&self) -> Vec<&Node> {
+        self.marked
+            .iter()
+            .filter(|path| !path.ancestors().skip(1).any(|a| self.marked.contains(a)))
+            .filter_map(|path| self.root.find(path))
+            .collect()
+    }
+
+    /// 标记条目数与去除嵌套后的合计大小
+    pub fn marked_total(&self) -> (usize, u64) {
+        let size = self.marked_roots().iter().map(|n| n.size()).sum();
+        (self.marked.len(), size)
+    }
+
+    /// 复制光标处条目（有标记时为所有标记条目）的完整路径，每行一个
+    fn copy_paths(&mut self) -> anyhow::Result<()> {
+        let paths: Vec<PathBuf> = if self.marked.is_empty() {
+            let view = self.flatten_view();
+            match view.get(self.cursor) {
+                Some(item) if item.node.is_synthetic() => {
+                    bail!("Select one of the scan roots first")
+                }
+                Some(item) => vec![item.node.path().to_path_buf()],
+                None => return Ok(()),
+            }
+        } else {
+            self.marked.iter().cloned().collect()
+        };
+        let paths = paths
+            .iter()
+            .map(|p| std::path::absolute(p).map(|p| p.to_string_lossy().into_owned()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        let method = clipboard::copy(&paths.join("\n"), self.config.clipboard.as_deref())?;
+        let label = match paths.as_slice() {
+            [path] => path.clone(),
+            _ => format!("{} paths", paths.len()),
+        };
+        self.status = Some(StatusMessage {
+            text: format!("Copied {label} ({method})"),
+            is_error: false,
+        });
+        Ok(())
+    }
+
+    /// 切换以逗号或空格分隔的列的显示
+    fn toggle_columns(&mut self, text: &str) -> anyhow::Result<()> {
+        let kinds = text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|name| !name.is_empty())
+            .map(ColumnKind::parse)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if kinds.is_empty() {
+            self.clear_status();
+            return Ok(());
+        }
+        let mut changes = Vec::new();
+        for kind in kinds {
+            let shown = toggle_column(&mut self.config.columns, kind)?;
+            let name = format!("{kind:?}").to_lowercase();
+            changes.push(format!(
+                "{} {name}",
+                if shown { "showing" } else { "hiding" }
+            ));
+        }
+        self.status = Some(StatusMessage {
+            text: format!("Columns: {}", changes.join(", ")),
+            is_error: false,
+        });
+        Ok(())
+    }
+
+    /// 打开导出标记列表的输入框
+    fn start_export(&mut self) -> anyhow::Result<()> {
+        if self.marked.is_empty() {
+            bail!("Nothing marked (space to mark)");
+        }
+        self.prompt = Some(Prompt {
+            kind: PromptKind::Export,
+            text: String::new(),
+        });
+        Ok(())
+    }
+
+    /// 将标记的路径逐行写入文件
+    fn export_marks(&mut self, target: &str) -> anyhow::Result<()> {
+        if target.is_empty() {
+            bail!("No file name given");
+        }
+        let mut out = String::new();
+        for path in &self.marked {
+            out.push_str(&path.to_string_lossy());
+            out.push('\n');
+        }
+        std::fs::write(target, out).map_err(|e| anyhow::anyhow!("Cannot write {target}: {e}"))?;
+        self.status = Some(StatusMessage {
+            text: format!("Wrote {} paths to {target}", self.marked.len()),
+            is_error: false,
+        });
+        Ok(())
+    }
+
+    /// 请求删除或移到回收站，打开确认提示
+    ///
+    /// 有标记时作用于所有标记条目，否则作用于光标处条目。
+    fn request_removal(&mut self, removal: Removal) -> anyhow::Result<()> {
+        let targets: Vec<&Node> = if self.marked.is_empty() {
+            let view = self.flatten_view();
+            match view.get(self.cursor) {
+                Some(item) => vec![item.node],
+                None => return Ok(()),
+            }
+        } else {
+            self.marked_roots()
+        };
+        if targets.iter().any(|n| self.is_scan_root(n.path())) {
+            bail!("Refusing to delete the scan root");
+        }
+        if targets.iter().any(|n| self.view_root.starts_with(n.path())) {
+            bail!("Zoom out (<) before removing the displayed root");
+        }
+
+        let size: u64 = targets.iter().map(|n| n.size()).sum();
+        let (dirs, files) = targets.iter().fold((0, 0), |(dirs, files), n| {
+            let (d, f) = n.counts();
+            match n.kind() {
+                Directory(_) => (dirs + d + 1, files + f),
+                File => (dirs + d, files + f + 1),
+            }
+        });
+        let (label, detail) = match targets.as_slice() {
+            [node] => (
+                node.path().display().to_string(),
+                match node.kind() {
+                    Directory(_) => format!(
+                        "{}, {} dirs, {files} files",
+                        self.size_format.format(size),
+                        dirs - 1
+                    ),
+                    File => self.size_format.format(size),
+                },
+            ),
+            _ => (
+                format!("{} marked entries", targets.len()),
+                format!(
+                    "{}, {dirs} dirs, {files} files",
+                    self.size_format.format(size)
+                ),
+            ),
+        };
+        // 删除只作用于扫描到的条目，未扫描的条目会留在磁盘上
+        let kept = match (removal, &self.scan_options) {
+            (Removal::Trash, _) => "",
+            (Removal::Delete, None) => "; unlisted entries are kept",
+            (Removal::Delete, Some(options)) if !options.exclude.is_empty() => {
+                "; excluded entries are kept"
+            }
+            (Removal::Delete, Some(_)) => "",
+        };
+        let text = format!(
+            "{}{} {label} ({detail}{kept}){}? [y/N]",
+            if self.dry_run { "[dry run] " } else { "" },
+            match removal {
+                Removal::Delete => "Delete",
+                Removal::Trash => "Move",
+            },
+            match removal {
+                Removal::Delete => "",
+                Removal::Trash => " to trash",
+            }
+        );
+        let paths = targets.iter().map(|n| n.path().to_path_buf()).collect();
+        self.pending_removal = Some((removal, paths));
+        self.prompt = Some(Prompt {
+            kind: PromptKind::Confirm,
+            text,
+        });
+        Ok(())
+    }
+
+    /// 删除或移到回收站已确认的条目，并就地更新树
+    fn remove(&mut self, removal: Removal, paths: &[PathBuf]) -> anyhow::Result<()> {
+        let label = match paths {
+            [path] => path.display().to_string(),
+            _ => format!("{} entries", paths.len()),
+        };
+        if self.dry_run {
+            let (items, size) = paths.iter().filter_map(|p| self.root.find(p)).fold(
+                (0, 0),
+                |(items, size), node| {
+                    let (dirs, files) = node.counts();
+                    (items + dirs + files + 1, size + node.size())
+                },
+            );
+            self.status = Some(StatusMessage {
+                text: format!(
+                    "Dry run: would {} {label} ({}, {items} items)",
+                    match removal {
+                        Removal::Delete => "delete",
+                        Removal::Trash => "move to trash",
+                    },
+                    self.size_format.format(size)
+                ),
+                is_error: false,
+            });
+            return Ok(());
+        }
+
+        // 逐个条目处理，单个失败不影响其余条目
+        let mut errors: Vec<String> = Vec::new();
+        let mut trashes: Vec<PathBuf> = Vec::new();
+        let mut freed = 0;
+        for path in paths {
+            match removal {
+                Removal::Delete => {
+                    let Some(node) = self.root.find(path) else {
+                        continue;
+                    };
+                    let mut failures: Vec<Failure> = Vec::new();
+                    remove_tree(node, &mut failures);
+                    errors.extend(
+                        failures
+                            .iter()
+                            .map(|f| format!("{}: {}", f.path.display(), f.error)),
+                    );
+                }
+                Removal::Trash => match move_to_trash(path) {
+                    Ok(trash) if !trashes.contains(&trash) => trashes.push(trash),
+                    Ok(_) => {}
+                    Err(e) => errors.push(format!("{e:#}")),
+                },
+            }
+            freed += self.root.prune_missing(path).0;
+        }
+        let root = &self.root;
+        self.marked.retain(|p| root.find(p).is_some());
+        self.tree_changed();
+
+        if !errors.is_empty() {
+            bail!(
+                "{} freed; {} entries could not be {}: {}",
+                self.size_format.format(freed),
+                errors.len(),
+                match removal {
+                    Removal::Delete => "deleted",
+                    Removal::Trash => "moved to trash",
+                },
+                errors.join("; ")
+            );
+        }
+        let text = match removal {
+            Removal::Delete => {
+                format!("Deleted {label} ({} freed)", self.size_format.format(freed))
+            }
+            Removal::Trash => format!(
+                "Moved {label} to {} ({})",
+                trashes
+                    .iter()
+                    .map(|t| t.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                self.size_format.format(freed)
+            ),
+        };
+        self.status = Some(StatusMessage {
+            text,
+            is_error: false,
+        });
+        Ok(())
+    }
+
+    /// 对光标处条目运行自定义命令，在状态栏显示输出的第一行或退出状态
+    fn run_custom(&mut self, index: usize) -> anyhow::Result<()> {
+        let Some(command) = self.config.commands.get(index).cloned() else {
+            return Ok(());
+        };
+        let view = self.flatten_view();
+        let Some(item) = view.get(self.cursor) else {
+            return Ok(());
+        };
+        let script = expand_command(&command.cmd, item.node)?;
+        let path = item.node.path().to_path_buf();
+        let output = run_captured(&script)
+            .with_context(|| format!("Cannot run command for key `{}`", command.key))?;
+
+        let first_line = |bytes: &[u8]| {
+            String::from_utf8_lossy(bytes)
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_string)
+        };
+        let line = first_line(&output.stdout).or_else(|| first_line(&output.stderr));
+        let status = match output.status.code() {
+            Some(0) => "done".to_string(),
+            Some(code) => format!("exited with status {code}"),
+            None => "was terminated".to_string(),
+        };
+        let mut text = match line {
+            Some(line) => format!("`{}` {status}: {line}", command.key),
+            None => format!("`{}` {status}", command.key),
+        };
+        if command.rescan && self.scan_options.is_some() {
+            let dir = if self.is_scan_root(&path) {
+                path
+            } else {
+                parent_dir(&path)
+            };
+            self.rescan(&dir)
+                .with_context(|| format!("{text}; rescan failed"))?;
+            text.push_str(&format!("; rescanned {}", dir.display()));
+        }
+        self.status = Some(StatusMessage {
+            text,
+            is_error: !output.status.success(),
+        });
+        Ok(())
+    }
+
+    /// 外部程序退出后报告状态，并按需重新扫描其子树
+    pub fn finish_spawn(&mut self, spawn: &Spawn, result: std::io::Result<ExitStatus>) {
+        let status = match result {
+            Ok(status) => status,
+            Err(e) => {
+                self.set_error(format!("Cannot run {}: {e}", spawn.program));
+                return;
+            }
+        };
+        let mut text = match status.code() {
+            Some(0) => format!("{} exited", spawn.program),
+            Some(code) => format!("{} exited with status {code}", spawn.program),
+            None => format!("{} was terminated", spawn.program),
+        };
+        if let Some(path) = &spawn.rescan
+            && self.scan_options.is_some()
+        {
+            match self.rescan(path) {
+                Ok(()) => text.push_str(&format!("; rescanned {}", path.display())),
+                Err(e) => {
+                    self.set_error(format!("{text}; rescan failed: {e}"));
+                    return;
+                }
+            }
+        }
+        self.status = Some(StatusMessage {
+            text,
+            is_error: !status.success(),
+        });
+    }
+
+    /// 重新扫描子树并替换树中的对应节点，已不存在时从树中移除
+    fn rescan(&mut self, path: &Path) -> anyhow::Result<()> {
+        let Some(options) = &self.scan_options else {
+            return Ok(());
+        };
+        if std::fs::symlink_metadata(path).is_err() {
+            self.root.prune_missing(path);
+        } else {
+            let root = self.scan_root_of(path).unwrap_or(path);
+            let node = Node::rescan(path.to_path_buf(), root, options)?;
+            self.root.replace(node);
+        }
+        let root = &self.root;
+        self.marked.retain(|p| root.find(p).is_some());
+        self.tree_changed();
+        Ok(())
+    }
+
+    /// 树被修改后丢弃失效的搜索结果，重新计算过滤并修正光标
+    fn tree_changed(&mut self) {
+        self.search = None;
+        if let Some(text) = self.filter.as_ref().map(|f| f.filter.text.clone()) {
+            self.apply_filter(&text).ok();
+        }
+        let view_len = self.flatten_view().len();
+        self.cursor = self.cursor.min(view_len.saturating_sub(1));
+    }
+
+    /// 处理确认提示：`y` 执行，其他输入取消
+    fn handle_confirm(&mut self, action: &Action) -> anyhow::Result<()> {
+        self.prompt = None;
+        let Some((removal, paths)) = self.pending_removal.take() else {
+            return Ok(());
+        };
+        match action {
+            Action::PromptInput('y' | 'Y') => self.remove(removal, &paths),
+            _ => {
+                self.status = Some(StatusMessage {
+                    text: "Cancelled".to_string(),
+                    is_error: false,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// 处理输入框动作
+    fn handle_prompt(&mut self, action: &Action) -> anyhow::Result<()> {
+        let Some(prompt) = &mut self.prompt else {
+            return Ok(());
+        };
+        let kind = prompt.kind;
+        if kind == PromptKind::Confirm {
+            return self.handle_confirm(action);
+        }
+        match action {
+            Action::PromptInput(ch) => prompt.text.push(*ch),
+            Action::PromptBackspace => {
+                prompt.text.pop();
+            }
+            Action::PromptSubmit => {
+                let text = std::mem::take(&mut prompt.text);
+                self.prompt = None;
+                match kind {
+                    PromptKind::Export => return self.export_marks(text.trim()),
+                    PromptKind::Columns => return self.toggle_columns(&text),
+                    _ => {}
+                }
+                self.filter_origin = None;
+                if self.search.as_ref().is_some_and(|s| s.matches.is_empty()) {
+                    self.search = None;
+                }
+                return Ok(());
+            }
+            Action::PromptCancel => {
+                self.prompt = None;
+                self.clear_status();
+                if let Some(search) = self.search.take() {
+                    self.cursor = search.origin_cursor;
+                    self.expanded_nodes = search.origin_expanded;
+                }
+                if let Some(origin) = self.filter_origin.take() {
+                    // 原表达式已解析过，恢复不会失败
+                    self.apply_filter(&origin).ok();
+                }
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+        match kind {
+            PromptKind::Search => match self.update_search() {
+                Ok(()) => self.set_match_status(),
+                // 正则尚未输入完整时保留上一次状态，仅提示
+                Err(e) => self.set_error(e.to_string()),
+            },
+            PromptKind::Confirm | PromptKind::Export | PromptKind::Columns => {}
+            PromptKind::Filter => {
+                let text = self
+                    .prompt
+                    .as_ref()
+                    .map(|p| p.text.clone())
+                    .unwrap_or_default();
+                match self.apply_filter(&text) {
+                    Ok(()) => self.clear_status(),
+                    // 表达式尚未输入完整时保留上一次过滤，仅提示
+                    Err(e) => self.set_error(e.to_string()),
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// 设置错误消息
     fn set_error(&mut self, message: impl Into<String>) {
         self.status = Some(StatusMessage {
@@ -139,16 +1220,14 @@ impl<'a> UiState<'a> {
         let item = view
             .get(index)
             .ok_or_else(|| anyhow::anyhow!("Index {index} not found!"))?;
-        let target_node = item.node;
-
-        if let File = target_node.kind() {
+        if let File = item.node.kind() {
             bail!("Cannot toggle file");
         }
+        let path = item.node.path().to_path_buf();
 
         // 切换展开状态
-        match self.expanded_nodes.iter().position(|&x| x == target_node) {
-            Some(idx) => { self.expanded_nodes.remove(idx); }
-            None => { self.expanded_nodes.push(target_node); }
+        if !self.expanded_nodes.remove(&path) {
+            self.expanded_nodes.insert(path);
         }
 
         // 调整光标
@@ -162,18 +1241,50 @@ impl<'a> UiState<'a> {
     /// 处理动作，返回是否继续运行
     pub fn update(&mut self, action: Action) -> anyhow::Result<bool> {
         let view_len = self.flatten_view().len();
+        // 确认只对紧接着的同一操作有效
+        let pending_expand = self.pending_expand.take();
 
         match action {
             Action::MoveUp => {
+                self.move_with_count(-1, view_len);
+                Ok(true)
+            }
+            Action::MoveDown => {
+                self.move_with_count(1, view_len);
+                Ok(true)
+            }
+            Action::PageUp => {
+                self.move_with_count(-(self.viewport_height.max(1) as isize), view_len);
+                Ok(true)
+            }
+            Action::PageDown => {
+                self.move_with_count(self.viewport_height.max(1) as isize, view_len);
+                Ok(true)
+            }
+            Action::HalfPageUp => {
+                self.move_with_count(-((self.viewport_height / 2).max(1) as isize), view_len);
+                Ok(true)
+            }
+            Action::HalfPageDown => {
+                self.move_with_count((self.viewport_height / 2).max(1) as isize, view_len);
+                Ok(true)
+            }
+            Action::Home => {
                 self.input_buffer.clear();
                 self.clear_status();
-                self.move_cursor(-1, view_len);
+                self.cursor = 0;
                 Ok(true)
             }
-            Action::MoveDown => {
+            Action::End => {
+                // 与 vim 的 `15G` 一致：有计数前缀时跳到该索引
+                let target = if self.input_buffer.is_empty() {
+                    view_len.saturating_sub(1)
+                } else {
+                    self.take_count().min(view_len.saturating_sub(1))
+                };
                 self.input_buffer.clear();
                 self.clear_status();
-                self.move_cursor(1, view_len);
+                self.cursor = target;
                 Ok(true)
             }
             Action::ToggleAtCursor => {
@@ -184,6 +1295,55 @@ impl<'a> UiState<'a> {
                 }
                 Ok(true)
             }
+            Action::CollapseOrParent => {
+                self.input_buffer.clear();
+                match self.collapse_or_parent() {
+                    Ok(()) => self.clear_status(),
+                    Err(e) => self.set_error(e.to_string()),
+                }
+                Ok(true)
+            }
+            Action::ExpandOrChild => {
+                self.input_buffer.clear();
+                match self.expand_or_child() {
+                    Ok(()) => self.clear_status(),
+                    Err(e) => self.set_error(e.to_string()),
+                }
+                Ok(true)
+            }
+            Action::ExpandSubtree => {
+                self.input_buffer.clear();
+                let view = self.flatten_view();
+                let Some(item) = view.get(self.cursor) else {
+                    return Ok(true);
+                };
+                if !item.node.kind().is_dir() {
+                    self.set_error("Cannot expand file");
+                    return Ok(true);
+                }
+                let target = ExpandTarget::Subtree(item.node.path().to_path_buf());
+                let confirmed = pending_expand.as_ref() == Some(&target);
+                match self.expand(target, confirmed) {
+                    Ok(()) => self.clear_status(),
+                    Err(e) => self.set_error(e.to_string()),
+                }
+                Ok(true)
+            }
+            Action::ExpandToDepth => {
+                let target = ExpandTarget::Depth(self.take_count());
+                let confirmed = pending_expand.as_ref() == Some(&target);
+                match self.expand(target, confirmed) {
+                    Ok(()) => self.clear_status(),
+                    Err(e) => self.set_error(e.to_string()),
+                }
+                Ok(true)
+            }
+            Action::CollapseAll => {
+                self.input_buffer.clear();
+                self.clear_status();
+                self.collapse_all();
+                Ok(true)
+            }
             Action::Toggle(index) => {
                 self.input_buffer.clear();
                 match self.toggle_by_index(index) {
@@ -242,7 +1402,195 @@ impl<'a> UiState<'a> {
                 };
                 Ok(true)
             }
+            Action::StartSearch => {
+                self.input_buffer.clear();
+                self.clear_status();
+                self.start_search();
+                Ok(true)
+            }
+            Action::NextMatch | Action::PrevMatch => {
+                self.input_buffer.clear();
+                if let Err(e) = self.jump_to_match(matches!(action, Action::NextMatch)) {
+                    self.set_error(e.to_string());
+                }
+                Ok(true)
+            }
+            Action::ZoomIn | Action::ZoomOut => {
+                self.input_buffer.clear();
+                let result = match action {
+                    Action::ZoomIn => self.zoom_in(),
+                    _ => self.zoom_out(),
+                };
+                match result {
+                    Ok(()) => self.clear_status(),
+                    Err(e) => self.set_error(e.to_string()),
+                }
+                Ok(true)
+            }
+            Action::StartFilter => {
+                self.input_buffer.clear();
+                self.clear_status();
+                self.search = None;
+                self.start_filter();
+                Ok(true)
+            }
+            Action::ToggleFilterSizes => {
+                self.input_buffer.clear();
+                if self.filter.is_none() {
+                    self.set_error("No active filter (press f to filter)");
+                    return Ok(true);
+                }
+                self.clear_status();
+                self.filtered_sizes = !self.filtered_sizes;
+                Ok(true)
+            }
+            Action::Delete | Action::Trash => {
+                self.input_buffer.clear();
+                self.clear_status();
+                let removal = match action {
+                    Action::Delete => Removal::Delete,
+                    _ => Removal::Trash,
+                };
+                if let Err(e) = self.request_removal(removal) {
+                    self.set_error(e.to_string());
+                }
+                Ok(true)
+            }
+            Action::ToggleMark => {
+                self.input_buffer.clear();
+                self.clear_status();
+                self.toggle_mark();
+                Ok(true)
+            }
+            Action::InvertMarks => {
+                self.input_buffer.clear();
+                self.clear_status();
+                self.invert_marks();
+                Ok(true)
+            }
+            Action::MarkChildren => {
+                self.input_buffer.clear();
+                match self.mark_children() {
+                    Ok(()) => self.clear_status(),
+                    Err(e) => self.set_error(e.to_string()),
+                }
+                Ok(true)
+            }
+            Action::ClearMarks => {
+                self.input_buffer.clear();
+                self.clear_status();
+                self.marked.clear();
+                Ok(true)
+            }
+            Action::ExportMarks => {
+                self.input_buffer.clear();
+                match self.start_export() {
+                    Ok(()) => self.clear_status(),
+                    Err(e) => self.set_error(e.to_string()),
+                }
+                Ok(true)
+            }
+            Action::Spawn(program) => {
+                self.input_buffer.clear();
+                self.clear_status();
+                let view = self.flatten_view();
+                let Some(item) = view.get(self.cursor) else {
+                    return Ok(true);
+                };
+                match Spawn::new(program, item.node) {
+                    Ok(spawn) => self.spawn = Some(spawn),
+                    Err(e) => self.set_error(e.to_string()),
+                }
+                Ok(true)
+            }
+            Action::Custom(index) => {
+                self.input_buffer.clear();
+                self.clear_status();
+                if let Err(e) = self.run_custom(index) {
+                    self.set_error(format!("{e:#}"));
+                }
+                Ok(true)
+            }
+            Action::CopyPath => {
+                self.input_buffer.clear();
+                self.clear_status();
+                if let Err(e) = self.copy_paths() {
+                    self.set_error(e.to_string());
+                }
+                Ok(true)
+            }
+            Action::PromptInput(_)
+            | Action::PromptBackspace
+            | Action::PromptSubmit
+            | Action::PromptCancel => {
+                self.handle_prompt(&action)?;
+                Ok(true)
+            }
+            Action::TogglePercent | Action::ToggleBar => {
+                self.input_buffer.clear();
+                self.clear_status();
+                let kind = match action {
+                    Action::TogglePercent => ColumnKind::Percent,
+                    _ => ColumnKind::Bar,
+                };
+                toggle_column(&mut self.config.columns, kind)?;
+                Ok(true)
+            }
+            Action::StartColumns => {
+                self.input_buffer.clear();
+                self.status = Some(StatusMessage {
+                    text: format!("Columns: {}", ColumnKind::NAMES),
+                    is_error: false,
+                });
+                self.prompt = Some(Prompt {
+                    kind: PromptKind::Columns,
+                    text: String::new(),
+                });
+                Ok(true)
+            }
+            Action::CycleUnits => {
+                self.input_buffer.clear();
+                self.size_format.units = self.size_format.units.next();
+                self.status = Some(StatusMessage {
+                    text: format!("Size units: {}", self.size_format.units.label()),
+                    is_error: false,
+                });
+                Ok(true)
+            }
+            Action::ToggleShareBase => {
+                self.input_buffer.clear();
+                self.share_base = match self.share_base {
+                    ShareBase::Parent => ShareBase::Root,
+                    ShareBase::Root => ShareBase::Parent,
+                };
+                Ok(true)
+            }
+            Action::ToggleDetails => {
+                self.input_buffer.clear();
+                self.show_details = !self.show_details;
+                self.update_viewport();
+                Ok(true)
+            }
+            Action::Resize(cols, rows) => {
+                self.terminal_size = (cols, rows);
+                self.update_viewport();
+                Ok(true)
+            }
             Action::Quit => Ok(false),
         }
     }
 }
+
+/// 收集子树中深度小于 `max_depth` 的目录（`node` 自身深度为 0）
+fn collect_dirs(node: &Node, max_depth: usize, dirs: &mut HashSet<PathBuf>) {
+    let Directory(prop) = node.kind() else {
+        return;
+    };
+    if max_depth == 0 {
+        return;
+    }
+    dirs.insert(node.path().to_path_buf());
+    for child in prop.children() {
+        collect_dirs(child, max_depth - 1, dirs);
+    }
+}